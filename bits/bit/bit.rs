@@ -1,3 +1,5 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
 use core::iter::successors;
 use core::ops::{Bound, Range, RangeBounds};
 
@@ -85,6 +87,33 @@ pub fn chunks(start: usize, end: usize, n: usize) -> impl Iterator<Item = (usize
     successors(step(start), move |&(index, len)| step(index + len))
 }
 
+/// Splits a given range [s, e) at multiples of n, and visits each piece as a
+/// `(block_index, in_block_range, is_full)` tuple, where `is_full` is true if the piece covers the whole block.
+///
+/// # Examples
+///
+/// ```
+/// let mut it = bit::aligned(10, 10, 8);
+/// assert_eq!(it.next(), None);
+///
+/// let mut it = bit::aligned(3, 5, 8);
+/// assert_eq!(it.next(), Some((0, 3..5, false)));
+/// assert_eq!(it.next(), None);
+///
+/// let mut it = bit::aligned(10, 33, 8);
+/// assert_eq!(it.next(), Some((1, 2..8, false)));
+/// assert_eq!(it.next(), Some((2, 0..8, true)));
+/// assert_eq!(it.next(), Some((3, 0..8, true)));
+/// assert_eq!(it.next(), Some((4, 0..1, false)));
+/// assert_eq!(it.next(), None);
+/// ```
+pub fn aligned(start: usize, end: usize, n: usize) -> impl Iterator<Item = (usize, Range<usize>, bool)> {
+    chunks(start, end, n).map(move |(index, len)| {
+        let (i, p) = addr(index, n);
+        (i, p..p + len, len == n)
+    })
+}
+
 // TODO: Use [usize::checked_next_multiple_of](https://doc.rust-lang.org/std/primitive.usize.html#method.checked_next_multiple_of).
 // https://github.com/rust-lang/rust/issues/88581
 #[inline]
@@ -103,4 +132,25 @@ mod tests {
         assert_eq!(next_multiple_of(23, 8), 24);
         assert_eq!(next_multiple_of(9, 3), 12);
     }
+
+    #[test]
+    fn aligned() {
+        for n in 1..10 {
+            for start in 0..40 {
+                for end in start..40 {
+                    // Group each bit in [start, end) by its block naively.
+                    let mut naive: Vec<(usize, std::ops::Range<usize>, bool)> = Vec::new();
+                    for i in start..end {
+                        match naive.last_mut() {
+                            Some((b, r, _)) if *b == i / n => r.end += 1,
+                            _ => naive.push((i / n, i % n..i % n + 1, false)),
+                        }
+                    }
+                    naive.iter_mut().for_each(|(_, r, full)| *full = r.len() == n);
+
+                    assert_eq!(super::aligned(start, end, n).collect::<Vec<_>>(), naive);
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,398 @@
+use std::ops::RangeBounds;
+
+use bits_buf::Buf;
+use bits_core::block::*;
+use bits_core::mask::helper;
+use bits_core::SpaceUsage;
+use smallset::SmallSet;
+
+/// A bits block of 65536 bits that switches its representation by the cardinality.
+///
+/// While the number of enabled bits is small, values are stored in a `SmallSet<u16, N>`.
+/// It is promoted to a dense `Buf<[u64; 1024]>` (8 KiB) once `count1()` exceeds [`PROMOTE`],
+/// and demoted back to `SmallSet` once `count1()` goes down to [`DEMOTE`].
+#[derive(Debug, Clone)]
+pub struct Adaptive<const N: usize>(Repr<N>);
+
+#[derive(Debug, Clone)]
+enum Repr<const N: usize> {
+    Sparse(SmallSet<u16, N>),
+    // Caches `count1()` of the buffer to decide when to demote without scanning.
+    Dense(Buf<[u64; WORDS]>, usize),
+}
+
+const WORDS: usize = 1024;
+
+/// `Adaptive` is promoted to the dense representation when `count1()` exceeds this value.
+///
+/// A `SmallSet<u16, N>` with 4096 elements occupies 8 KiB, which is the same size of a dense block.
+pub const PROMOTE: usize = 4096;
+
+/// `Adaptive` is demoted to the sparse representation when `count1()` goes down to this value.
+///
+/// Smaller than [`PROMOTE`] so that flipping a bit back and forth around the threshold
+/// does not convert the representation every time.
+pub const DEMOTE: usize = PROMOTE / 2;
+
+impl<const N: usize> Adaptive<N> {
+    /// Returns true if the block is currently stored as a dense bits.
+    ///
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_core::block::*;
+    /// # use bits_adaptive::{Adaptive, DEMOTE, PROMOTE};
+    /// let mut b = Adaptive::<8>::empty();
+    /// for i in 0..PROMOTE {
+    ///     b.set1(i * 2);
+    /// }
+    /// assert!(!b.is_dense());
+    ///
+    /// b.set1(1);
+    /// assert!(b.is_dense());
+    /// assert_eq!(b.count1(), PROMOTE + 1);
+    ///
+    /// for i in 0..PROMOTE - DEMOTE {
+    ///     b.set0(i * 2);
+    /// }
+    /// assert!(b.is_dense());
+    /// b.set0(1);
+    /// assert!(!b.is_dense());
+    /// assert_eq!(b.count1(), DEMOTE);
+    /// assert_eq!(b.select1(0), Some((PROMOTE - DEMOTE) * 2));
+    /// ```
+    #[inline]
+    pub fn is_dense(&self) -> bool {
+        matches!(self.0, Repr::Dense(..))
+    }
+
+    fn promote(set: &SmallSet<u16, N>) -> Repr<N> {
+        let mut buf = Buf::empty();
+        for &i in set.as_ref() {
+            buf.set1(i as usize);
+        }
+        Repr::Dense(buf, set.count1())
+    }
+
+    fn demote(buf: &Buf<[u64; WORDS]>) -> Repr<N> {
+        let words = buf.as_bits().as_slice().iter().enumerate();
+        let ones = words.flat_map(|(i, &w)| {
+            let mut w = w;
+            std::iter::from_fn(move || {
+                (w != 0).then(|| {
+                    let k = w.trailing_zeros() as usize;
+                    w &= w - 1;
+                    i * 64 + k
+                })
+            })
+        });
+        Repr::Sparse(SmallSet::from_sorted_iter(ones))
+    }
+
+    /// Switches the representation if the cardinality crossed the thresholds.
+    fn normalize(&mut self) {
+        match &self.0 {
+            Repr::Sparse(set) if set.count1() > PROMOTE => self.0 = Self::promote(set),
+            Repr::Dense(buf, ones) if *ones <= DEMOTE => self.0 = Self::demote(buf),
+            _ => {}
+        }
+    }
+
+    /// Recomputes the cached `count1()` and switches the representation if needed.
+    fn recount(&mut self) {
+        if let Repr::Dense(buf, ones) = &mut self.0 {
+            *ones = buf.count1();
+        }
+        self.normalize();
+    }
+}
+
+impl<const N: usize> Block for Adaptive<N> {
+    const BITS: usize = 1 << 16;
+
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_core::block::*;
+    /// # use bits_adaptive::Adaptive;
+    /// let b = Adaptive::<4>::empty();
+    /// assert_eq!(Adaptive::<4>::BITS, 65536);
+    /// assert_eq!(b.count1(), 0);
+    /// assert_eq!(b.test(0), Some(false));
+    /// assert_eq!(b.test(65536), None);
+    /// ```
+    #[inline]
+    fn empty() -> Self {
+        Adaptive(Repr::Sparse(SmallSet::empty()))
+    }
+
+    #[inline]
+    fn test(&self, i: usize) -> Option<bool> {
+        (i < Self::BITS).then(|| match &self.0 {
+            Repr::Sparse(set) => set.test(i).unwrap_or_default(),
+            Repr::Dense(buf, _) => buf.test(i).unwrap_or_default(),
+        })
+    }
+}
+
+impl<const N: usize> BlockMut for Adaptive<N> {
+    #[inline]
+    fn set1(&mut self, i: usize) {
+        assert!(i < Self::BITS);
+        match &mut self.0 {
+            Repr::Sparse(set) => set.set1(i),
+            Repr::Dense(buf, ones) => {
+                if !buf.test(i).unwrap_or_default() {
+                    buf.set1(i);
+                    *ones += 1;
+                }
+            }
+        }
+        self.normalize();
+    }
+
+    #[inline]
+    fn set0(&mut self, i: usize) {
+        assert!(i < Self::BITS);
+        match &mut self.0 {
+            Repr::Sparse(set) => set.set0(i),
+            Repr::Dense(buf, ones) => {
+                if buf.test(i).unwrap_or_default() {
+                    buf.set0(i);
+                    *ones -= 1;
+                }
+            }
+        }
+        self.normalize();
+    }
+}
+
+impl<const N: usize> Count for Adaptive<N> {
+    #[inline]
+    fn count1(&self) -> usize {
+        match &self.0 {
+            Repr::Sparse(set) => set.count1(),
+            Repr::Dense(_, ones) => *ones,
+        }
+    }
+}
+
+impl<const N: usize> Rank for Adaptive<N> {
+    #[inline]
+    fn rank1<R: RangeBounds<usize>>(&self, r: R) -> usize {
+        match &self.0 {
+            Repr::Sparse(set) => set.rank1(r),
+            Repr::Dense(buf, _) => buf.rank1(r),
+        }
+    }
+}
+
+impl<const N: usize> Select for Adaptive<N> {
+    #[inline]
+    fn select1(&self, n: usize) -> Option<usize> {
+        match &self.0 {
+            Repr::Sparse(set) => set.select1(n),
+            Repr::Dense(buf, _) => buf.select1(n),
+        }
+    }
+
+    #[inline]
+    fn select0(&self, n: usize) -> Option<usize> {
+        match &self.0 {
+            Repr::Sparse(set) => set.select0(n),
+            Repr::Dense(buf, _) => buf.select0(n),
+        }
+    }
+}
+
+impl<const N: usize> Pack for Adaptive<N> {}
+
+impl<const N: usize> SpaceUsage for Adaptive<N> {
+    #[inline]
+    fn heap_bytes(&self) -> usize {
+        match &self.0 {
+            Repr::Sparse(set) => set.heap_bytes(),
+            Repr::Dense(buf, _) => buf.heap_bytes(),
+        }
+    }
+}
+
+impl<const N: usize, const M: usize> helper::Assign<Adaptive<M>> for Adaptive<N> {
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_core::block::*;
+    /// # use bits_core::mask::helper::Assign;
+    /// # use bits_adaptive::Adaptive;
+    /// let mut a = Adaptive::<4>::empty();
+    /// let mut b = Adaptive::<4>::empty();
+    /// for i in 0..10000 {
+    ///     b.set1(i);
+    /// }
+    /// a.set1(1);
+    /// a.set1(20000);
+    /// assert!(!a.is_dense() && b.is_dense());
+    ///
+    /// let mut c = a.clone();
+    /// Assign::and(&mut c, &b);
+    /// assert_eq!(c.count1(), 1);
+    ///
+    /// let mut c = b.clone();
+    /// Assign::and(&mut c, &a);
+    /// assert_eq!(c.count1(), 1);
+    /// assert!(!c.is_dense());
+    /// ```
+    fn and(a: &mut Self, b: &Adaptive<M>) {
+        match (&mut a.0, &b.0) {
+            (Repr::Sparse(x), Repr::Sparse(y)) => helper::Assign::and(x, y),
+            (Repr::Dense(x, _), Repr::Dense(y, _)) => helper::Assign::and(x, y),
+            (Repr::Sparse(x), Repr::Dense(y, _)) => {
+                *x = retain(x, |i| y.test(i).unwrap_or_default());
+            }
+            (Repr::Dense(x, _), Repr::Sparse(y)) => {
+                a.0 = Repr::Sparse(retain(y, |i| x.test(i).unwrap_or_default()));
+            }
+        }
+        a.recount();
+    }
+
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_core::block::*;
+    /// # use bits_core::mask::helper::Assign;
+    /// # use bits_adaptive::Adaptive;
+    /// let mut a = Adaptive::<4>::empty();
+    /// let mut b = Adaptive::<4>::empty();
+    /// for i in 0..10000 {
+    ///     b.set1(i);
+    /// }
+    /// a.set1(1);
+    /// a.set1(20000);
+    ///
+    /// let mut c = a.clone();
+    /// Assign::not(&mut c, &b);
+    /// assert_eq!(c.select1(0), Some(20000));
+    /// assert_eq!(c.count1(), 1);
+    ///
+    /// let mut c = b.clone();
+    /// Assign::not(&mut c, &a);
+    /// assert_eq!(c.count1(), 9999);
+    /// assert_eq!(c.test(1), Some(false));
+    /// ```
+    fn not(a: &mut Self, b: &Adaptive<M>) {
+        match (&mut a.0, &b.0) {
+            (Repr::Sparse(x), Repr::Sparse(y)) => helper::Assign::not(x, y),
+            (Repr::Dense(x, _), Repr::Dense(y, _)) => helper::Assign::not(x, y),
+            (Repr::Sparse(x), Repr::Dense(y, _)) => {
+                *x = retain(x, |i| !y.test(i).unwrap_or_default());
+            }
+            (Repr::Dense(x, _), Repr::Sparse(y)) => {
+                for &i in y.as_ref() {
+                    x.set0(i as usize);
+                }
+            }
+        }
+        a.recount();
+    }
+
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_core::block::*;
+    /// # use bits_core::mask::helper::Assign;
+    /// # use bits_adaptive::Adaptive;
+    /// let mut a = Adaptive::<4>::empty();
+    /// let mut b = Adaptive::<4>::empty();
+    /// for i in 0..10000 {
+    ///     b.set1(i);
+    /// }
+    /// a.set1(1);
+    /// a.set1(20000);
+    ///
+    /// let mut c = a.clone();
+    /// Assign::or(&mut c, &b);
+    /// assert!(c.is_dense());
+    /// assert_eq!(c.count1(), 10001);
+    ///
+    /// let mut c = b.clone();
+    /// Assign::or(&mut c, &a);
+    /// assert_eq!(c.count1(), 10001);
+    /// ```
+    fn or(a: &mut Self, b: &Adaptive<M>) {
+        match (&mut a.0, &b.0) {
+            (Repr::Sparse(x), Repr::Sparse(y)) => helper::Assign::or(x, y),
+            (Repr::Dense(x, _), Repr::Dense(y, _)) => helper::Assign::or(x, y),
+            (Repr::Sparse(x), Repr::Dense(y, ones)) => {
+                let mut buf = y.clone();
+                for &i in x.as_ref() {
+                    buf.set1(i as usize);
+                }
+                a.0 = Repr::Dense(buf, *ones);
+            }
+            (Repr::Dense(x, _), Repr::Sparse(y)) => {
+                for &i in y.as_ref() {
+                    x.set1(i as usize);
+                }
+            }
+        }
+        a.recount();
+    }
+
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_core::block::*;
+    /// # use bits_core::mask::helper::Assign;
+    /// # use bits_adaptive::Adaptive;
+    /// let mut a = Adaptive::<4>::empty();
+    /// let mut b = Adaptive::<4>::empty();
+    /// for i in 0..10000 {
+    ///     b.set1(i);
+    /// }
+    /// a.set1(1);
+    /// a.set1(20000);
+    ///
+    /// let mut c = a.clone();
+    /// Assign::xor(&mut c, &b);
+    /// assert_eq!(c.count1(), 10000);
+    /// assert_eq!(c.test(1), Some(false));
+    /// assert_eq!(c.test(20000), Some(true));
+    ///
+    /// let mut c = b.clone();
+    /// Assign::xor(&mut c, &b);
+    /// assert_eq!(c.count1(), 0);
+    /// assert!(!c.is_dense());
+    /// ```
+    fn xor(a: &mut Self, b: &Adaptive<M>) {
+        match (&mut a.0, &b.0) {
+            (Repr::Sparse(x), Repr::Sparse(y)) => helper::Assign::xor(x, y),
+            (Repr::Dense(x, _), Repr::Dense(y, _)) => helper::Assign::xor(x, y),
+            (Repr::Sparse(x), Repr::Dense(y, ones)) => {
+                let mut buf = y.clone();
+                for &i in x.as_ref() {
+                    flip(&mut buf, i as usize);
+                }
+                a.0 = Repr::Dense(buf, *ones);
+            }
+            (Repr::Dense(x, _), Repr::Sparse(y)) => {
+                for &i in y.as_ref() {
+                    flip(x, i as usize);
+                }
+            }
+        }
+        a.recount();
+    }
+}
+
+fn retain<const N: usize, const M: usize>(set: &SmallSet<u16, M>, f: impl Fn(usize) -> bool) -> SmallSet<u16, N> {
+    SmallSet::from_sorted_iter(set.as_ref().iter().map(|&i| i as usize).filter(|&i| f(i)))
+}
+
+fn flip(buf: &mut Buf<[u64; WORDS]>, i: usize) {
+    if buf.test(i).unwrap_or_default() {
+        buf.set0(i);
+    } else {
+        buf.set1(i);
+    }
+}
@@ -1,19 +1,51 @@
-use std::cmp;
-use std::iter::Sum;
-use std::ops::RangeBounds;
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp;
+use core::iter::Sum;
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut, RangeBounds};
 
 use bits_core::block::{Block, *};
-use bits_core::word::Word;
-use bits_core::{BitVec, Bits};
+use bits_core::{BitLen, BitVec, Bits, BitsError, RankSelect, SpaceUsage};
 use fenwicktree::{LowerBound, Nodes, Prefix};
 
+mod elias_fano;
 mod l1l2;
+mod rank9;
+
+pub use elias_fano::EliasFano;
+pub use l1l2::{Layout, WideL1L2, L1L2};
+pub use rank9::Rank9;
 
 /// `Pop<T>` stores auxiliary data to compute `Rank` and `Select` more efficiently.
+///
+/// The bits are owned by `R`, which is a `BitVec<T>` by default.
+/// Any `R` dereferencing to `Bits<T>` works for queries, such as `&Bits<T>` borrowed
+/// from a memory-mapped file, and `R` needs `DerefMut` for updates.
+///
+/// `L` is the [`Layout`] of the counts per super block. The default [`L1L2`] is compact,
+/// but cannot count an upper block of `2^32` bits all set, which [`WideL1L2`] can.
+/// Building or updating a `Pop` that does not fit in `L` fails with [`BitsError::Overflow`].
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Pop<T> {
-    aux: Aux,
-    repr: BitVec<T>,
+pub struct Pop<T, R = BitVec<T>, L = L1L2> {
+    aux: Aux<L>,
+    repr: R,
+    _block: PhantomData<T>,
+}
+
+/// Heap bytes used by each part of [`Pop`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PopSpace {
+    /// The bits themselves.
+    pub repr: usize,
+    /// Fenwick tree of the counts per upper block.
+    pub ubs: usize,
+    /// Fenwick trees of the counts per super block and basic block.
+    pub lbs: usize,
 }
 
 // pub type PopVec<T> = Pop<Vec<T>>;
@@ -24,9 +56,9 @@ pub struct Pop<T> {
 // It seems good to try the latter to see if efficiency improves.
 // * [Engineering Compact Data Structures for Rank and Select Queries on Bit Vectors](https://arxiv.org/pdf/2206.01149)
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct Aux {
+struct Aux<L> {
     ubs: Vec<u64>,
-    lbs: Vec<l1l2::L1L2>,
+    lbs: Vec<L>,
 }
 
 const UPPER_BLOCK: usize = 1 << 32;
@@ -36,43 +68,49 @@ const BASIC_BLOCK: usize = 1 << 9; // 512 bits block
 
 const MAX_SB_LEN: usize = UPPER_BLOCK / SUPER_BLOCK;
 
-fn build<'a, T, I>(size: usize, super_blocks: I) -> Aux
-where
-    T: Word + 'a,
-    I: IntoIterator<Item = Option<&'a [T]>>,
-{
-    let mut aux = Aux::new(size);
+fn build<T: Block + Rank, L: Layout>(bits: &Bits<T>) -> Result<Aux<L>, BitsError> {
+    let mut aux = Aux::new(bits.bits());
 
-    for (i, sb) in super_blocks.into_iter().enumerate() {
-        let (bbs, sum) = basic_blocks(sb);
+    for (i, sb) in (0..bits.bits()).step_by(SUPER_BLOCK).enumerate() {
+        let (bbs, sum) = basic_blocks(bits, sb);
 
         let (q, r) = (i / MAX_SB_LEN, i % MAX_SB_LEN);
 
         // +1 to skip dummy index
         aux.ubs[q + 1] += sum;
-        aux.lb_mut(q)[r + 1] = l1l2::L1L2::merge([sum, bbs[0], bbs[1], bbs[2]]);
+        aux.lb_mut(q)[r + 1] = L::merge([sum, bbs[0], bbs[1], bbs[2]]);
+    }
+
+    // The root of the fenwick tree of an upper block holds the count of the whole upper block.
+    if aux.ubs.iter().any(|&count| count > L::L1_MAX) {
+        return Err(BitsError::Overflow);
+    }
+
+    // initialize upper_blocks as a binary index tree
+    fenwicktree::build(&mut aux.ubs);
+
+    // initialize lower_blocks as a binary index tree
+    for q in 0..aux.lb_parts() {
+        L::build(aux.lb_mut(q));
     }
 
-    aux
+    Ok(aux)
 }
 
-fn basic_blocks<W: Word>(sb: Option<&[W]>) -> ([u64; l1l2::LEN], u64) {
+// Counts each basic block in the super block starting at `sb` by `rank1`,
+// so that blocks larger or smaller than a basic block can be used.
+fn basic_blocks<T: Block + Rank>(bits: &Bits<T>, sb: usize) -> ([u64; l1l2::LEN], u64) {
     let mut bbs = [0; l1l2::LEN];
     let mut sum = 0;
-    if let Some(sb) = sb {
-        for (i, bb) in sb.chunks(BASIC_BLOCK / W::BITS).enumerate() {
-            let count1 = Bits::new(bb).count1() as u64;
-            bbs[i] = count1;
-            sum += count1;
-        }
+    let end = cmp::min(sb + SUPER_BLOCK, bits.bits());
+    for (i, bb) in (sb..end).step_by(BASIC_BLOCK).enumerate() {
+        let count1 = bits.rank1(bb..cmp::min(bb + BASIC_BLOCK, end)) as u64;
+        bbs[i] = count1;
+        sum += count1;
     }
     (bbs, sum)
 }
 
-fn super_blocks_from_words<T: Word>(slice: &[T]) -> impl Iterator<Item = Option<&[T]>> {
-    slice.chunks(SUPER_BLOCK / T::BITS).map(Some)
-}
-
 fn ubs_len(n: usize) -> usize {
     bit::blocks(n, UPPER_BLOCK) + 1
 }
@@ -92,40 +130,127 @@ fn lbs_len(n: usize) -> usize {
     }
 }
 
-impl<T: Word> From<Vec<T>> for Pop<T> {
+impl<T: Block + Rank> From<Vec<T>> for Pop<T> {
+    /// Builds the auxiliary data over any blocks.
+    ///
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_aux::Pop;
+    /// # use bits_core::block::*;
+    /// let mut blocks = vec![[0u64; 2]; 20];
+    /// blocks[3].set1(100);
+    /// blocks[17].set1(0);
+    /// blocks[17].set1(127);
+    /// let pop = Pop::from(blocks);
+    /// assert_eq!(pop.count1(), 3);
+    /// assert_eq!(pop.rank1(..128 * 17), 1);
+    /// assert_eq!(pop.select1(2), Some(128 * 17 + 127));
+    /// assert_eq!(pop.select0(484), Some(485));
+    /// ```
     fn from(repr: Vec<T>) -> Self {
-        let mut aux = build(Bits::new(&repr).bits(), super_blocks_from_words(&repr));
-
-        // TODO: should be in the [`build`] loop.
-        {
-            // initialize upper_blocks as a binary index tree
-            fenwicktree::build(&mut aux.ubs);
+        Pop::from_repr(BitVec::from(repr))
+    }
+}
 
-            // initialize lower_blocks as a binary index tree
-            for q in 0..aux.lb_parts() {
-                fenwicktree::build(aux.lb_mut(q));
-            }
+impl<T: Block + Rank, R: Deref<Target = Bits<T>>> Pop<T, R> {
+    /// Builds the auxiliary data over the bits of `repr`, without copying them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an upper block has `2^32` bits all set. Use [`Pop::try_from_repr`] with [`WideL1L2`] instead.
+    ///
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_aux::Pop;
+    /// # use bits_core::Bits;
+    /// let words = [0b1010u64, 0, 1 << 63];
+    /// let pop = Pop::from_repr(Bits::new(&words));
+    /// assert_eq!(pop.count1(), 3);
+    /// assert_eq!(pop.rank1(..64), 2);
+    /// assert_eq!(pop.select1(2), Some(191));
+    /// assert_eq!(pop.select0(2), Some(4));
+    /// ```
+    pub fn from_repr(repr: R) -> Pop<T, R> {
+        match Pop::try_from_repr(repr) {
+            Ok(pop) => pop,
+            Err(err) => panic!("{err}: too many ones to count in L1L2"),
         }
+    }
+}
 
-        Pop { aux, repr: BitVec::from(repr) }
+impl<T: Block + Rank, R: Deref<Target = Bits<T>>, L: Layout> Pop<T, R, L> {
+    /// Builds the auxiliary data in the layout `L`,
+    /// or returns [`BitsError::Overflow`] if the counts do not fit in `L`.
+    ///
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_aux::{Pop, WideL1L2};
+    /// # use bits_core::BitVec;
+    /// let mut bits = BitVec::<u64>::new(5000);
+    /// bits.set1(3);
+    /// bits.set1(4096);
+    /// let pop = Pop::<_, _, WideL1L2>::try_from_repr(bits).unwrap();
+    /// assert_eq!(pop.count1(), 2);
+    /// assert_eq!(pop.rank1(..4096), 1);
+    /// assert_eq!(pop.select1(1), Some(4096));
+    /// assert_eq!(pop.select0(3), Some(4));
+    /// ```
+    pub fn try_from_repr(repr: R) -> Result<Pop<T, R, L>, BitsError> {
+        let aux = build(&repr)?;
+        Ok(Pop { aux, repr, _block: PhantomData })
     }
 }
 
-impl<T: Block> Pop<T> {
+impl<T: Block, L: Layout> Pop<T, BitVec<T>, L> {
     #[inline]
-    pub fn new(n: usize) -> Pop<T> {
+    pub fn new(n: usize) -> Pop<T, BitVec<T>, L> {
         let repr = BitVec::new(n);
-        Pop { aux: Aux::new(repr.bits()), repr }
+        Pop { aux: Aux::new(repr.bits()), repr, _block: PhantomData }
     }
 }
 
-impl<T> Pop<T> {
+impl<T, R: Deref<Target = Bits<T>>, L> Pop<T, R, L> {
     pub fn inner(&self) -> &Bits<T> {
         &self.repr
     }
+
+    /// Drops the auxiliary data and returns the bits.
+    pub fn into_repr(self) -> R {
+        self.repr
+    }
+}
+
+impl<T: SpaceUsage, L: Layout> Pop<T, BitVec<T>, L> {
+    /// Returns heap bytes used by the bits and by the auxiliary data separately.
+    ///
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_aux::Pop;
+    /// # use bits_core::SpaceUsage;
+    /// let pop = Pop::from(vec![0u64; 1024]);
+    /// let space = pop.space();
+    /// assert_eq!(space.repr, 8192);
+    /// assert!(space.ubs > 0 && space.lbs > 0);
+    /// assert_eq!(pop.heap_bytes(), space.repr + space.ubs + space.lbs);
+    /// ```
+    pub fn space(&self) -> PopSpace {
+        PopSpace { repr: self.repr.heap_bytes(), ubs: self.aux.ubs.heap_bytes(), lbs: self.aux.lbs.heap_bytes() }
+    }
 }
 
-impl<T: Block> Pop<T> {
+impl<T: SpaceUsage, L: Layout> SpaceUsage for Pop<T, BitVec<T>, L> {
+    #[inline]
+    fn heap_bytes(&self) -> usize {
+        let PopSpace { repr, ubs, lbs } = self.space();
+        repr + ubs + lbs
+    }
+}
+
+impl<T: Block, R: Deref<Target = Bits<T>>, L> Pop<T, R, L> {
     #[inline]
     pub fn bits(&self) -> usize {
         self.repr.bits()
@@ -137,7 +262,7 @@ impl<T: Block> Pop<T> {
     }
 }
 
-impl<T: Block> Pop<T> {
+impl<T: Block, R: Deref<Target = Bits<T>>, L> Pop<T, R, L> {
     #[inline]
     pub fn count1(&self) -> usize {
         let ubs = &self.aux.ubs;
@@ -148,11 +273,18 @@ impl<T: Block> Pop<T> {
     pub fn count0(&self) -> usize {
         self.bits() - self.count1()
     }
+
+    /// Counts occurrences of `1`, or returns an error if it does not fit in `usize`.
+    #[inline]
+    pub fn try_count1(&self) -> Result<usize, BitsError> {
+        let ubs = &self.aux.ubs;
+        num::cast::<u64, usize>(ubs.sum(ubs.nodes())).ok_or(BitsError::Overflow)
+    }
 }
 
-impl<T: Block + Rank> Pop<T> {
+impl<T: Block + Rank, R: Deref<Target = Bits<T>>, L: Layout> Pop<T, R, L> {
     pub fn rank1<Idx: RangeBounds<usize>>(&self, index: Idx) -> usize {
-        fn rank1_impl<U: Block + Rank>(me: &Pop<U>, p0: usize) -> usize {
+        fn rank1_impl<U: Block + Rank, S: Deref<Target = Bits<U>>, M: Layout>(me: &Pop<U, S, M>, p0: usize) -> usize {
             if p0 == 0 {
                 0
             } else if p0 == me.bits() {
@@ -165,102 +297,175 @@ impl<T: Block + Rank> Pop<T> {
                 let hi = &me.aux.ubs;
                 let lo = me.aux.lb(q0);
                 let c0: u64 = hi.sum(q0);
-                let c1: u64 = lo.sum(q1);
+                let c1: u64 = M::sum(lo, q1);
                 let c2 = lo[q1 + 1].l2_sum(q2);
                 num::cast::<_, usize>(c0 + c1 + c2).expect("failed to cast from u64 to usize")
                     + me.repr.rank1(p0 - r2..p0)
             }
         }
-        use std::ops::Range;
+        use core::ops::Range;
         let Range { start, end } = bit::bounded(&index, 0, self.bits());
         rank1_impl(self, end) - rank1_impl(self, start)
     }
 
     #[inline]
-    pub fn rank0<R: RangeBounds<usize>>(&self, r: R) -> usize {
+    pub fn rank0<Idx: RangeBounds<usize>>(&self, r: Idx) -> usize {
         let r = bit::bounded(&r, 0, self.bits());
         r.len() - self.rank1(r)
     }
+
+    /// Counts occurrences of `1` in `r`, or returns an error if `r` is out of bounds.
+    ///
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_aux::Pop;
+    /// # use bits_core::BitsError;
+    /// let mut pop = Pop::<u64>::new(100);
+    /// pop.try_set1(10).unwrap();
+    /// pop.try_set1(99).unwrap();
+    /// assert_eq!(pop.try_set1(128), Err(BitsError::OutOfBounds { index: 128, len: 128 }));
+    /// assert_eq!(pop.try_count1(), Ok(2));
+    /// assert_eq!(pop.try_rank1(11..), Ok(1));
+    /// assert_eq!(pop.try_rank1(11..200), Err(BitsError::InvalidRange { start: 11, end: 200, len: 128 }));
+    /// assert_eq!(pop.try_select1(1), Ok(99));
+    /// assert_eq!(pop.try_select1(2), Err(BitsError::OutOfBounds { index: 2, len: 2 }));
+    /// ```
+    #[inline]
+    pub fn try_rank1<Idx: RangeBounds<usize>>(&self, r: Idx) -> Result<usize, BitsError> {
+        BitsError::check_range(&r, self.bits()).map(|r| self.rank1(r))
+    }
 }
 
-impl<T: Block + Select + Pack> Pop<T> {
+impl<T: Block + Select, R: Deref<Target = Bits<T>>, L: Layout> Pop<T, R, L> {
+    /// Returns the position of the n-th 1, or an error if `n` is not less than `self.count1()`.
+    #[inline]
+    pub fn try_select1(&self, n: usize) -> Result<usize, BitsError> {
+        self.select1(n).ok_or_else(|| BitsError::OutOfBounds { index: n, len: self.count1() })
+    }
+
     pub fn select1(&self, n: usize) -> Option<usize> {
         let mut r = num::cast(n).expect("failed to cast from usize to u64");
 
         let (s, e) = {
             let p0 = find_l0(&self.aux.ubs[..], &mut r)?;
             let lo = self.aux.lb(p0);
-            let p1 = find_l1(lo, &mut r);
-            let ll = lo[p1 + 1];
-            let l2 = [ll.l2::<0>(), ll.l2::<1>(), ll.l2::<2>()];
+            let p1 = L::find1(lo, &mut r);
+            let [_, l2 @ ..] = lo[p1 + 1].split();
             let p2 = find_l2(&l2, &mut r);
 
             let s = p0 * UPPER_BLOCK + p1 * SUPER_BLOCK + p2 * BASIC_BLOCK;
             (s, cmp::min(s + BASIC_BLOCK, self.bits()))
         };
 
-        let mut r = r as usize;
+        let r = r as usize;
         {
             debug_assert!(n - r == self.rank1(..s));
             debug_assert!(r < self.rank1(s..e));
         }
 
-        // i + imp.bit_vec[x..y].select1(r).unwrap()
-
-        const BITS: usize = <u128 as Block>::BITS;
-        for i in (s..e).step_by(BITS) {
-            let b = self.repr.unpack::<u128>(i, BITS);
-            let c = b.count1();
-            if r < c {
-                // #[cfg(test)]
-                // {
-                //     dbg!(l0, l1, l2);
-                //     dbg!(lo[l1 + 1]);
-                //     dbg!(s, e);
-                // }
-                return Some(i + b.select1(r).unwrap());
-            }
-            r -= c;
-        }
-        unreachable!()
+        Some(self.repr.select1_in(s..e, r).expect("select1 must be ok"))
     }
 
     pub fn select0(&self, n: usize) -> Option<usize> {
+        // The complemented counts include the padding after the last bit, which are not zeros.
+        if n >= self.count0() {
+            return None;
+        }
         let mut r = num::cast(n).expect("failed to cast from usize to u64");
 
         let (s, e) = {
             const UB: u64 = UPPER_BLOCK as u64;
-            const SB: u64 = SUPER_BLOCK as u64;
             const BB: u64 = BASIC_BLOCK as u64;
             let hi_complemented = fenwicktree::complement(&self.aux.ubs[..], UB);
             let p0 = find_l0(&hi_complemented, &mut r)?;
             let lo = self.aux.lb(p0);
-            let lo_complemented = fenwicktree::complement(lo, SB);
-            let p1 = find_l1(&lo_complemented, &mut r);
-            let ll = lo[p1 + 1];
-            let l2 = [BB - ll.l2::<0>(), BB - ll.l2::<1>(), BB - ll.l2::<2>()];
+            let p1 = L::find0(lo, &mut r);
+            let [_, l2 @ ..] = lo[p1 + 1].split();
+            let l2 = l2.map(|c| BB - c);
             let p2 = find_l2(&l2, &mut r);
 
             let s = p0 * UPPER_BLOCK + p1 * SUPER_BLOCK + p2 * BASIC_BLOCK;
             (s, cmp::min(s + BASIC_BLOCK, self.bits()))
         };
 
-        let mut r = r as usize;
+        let r = r as usize;
         {
             debug_assert!(n - r == self.rank0(..s));
             debug_assert!(r < self.rank0(s..e));
         }
 
-        const BITS: usize = <u128 as Block>::BITS;
-        for i in (s..e).step_by(BITS) {
-            let b = self.repr.unpack::<u128>(i, BITS);
-            let c = b.count0();
-            if r < c {
-                return Some(i + b.select0(r).unwrap());
-            }
-            r -= c;
-        }
-        unreachable!()
+        Some(self.repr.select0_in(s..e, r).expect("select0 must be ok"))
+    }
+}
+
+impl<T: Block + Select, R: Deref<Target = Bits<T>>, L: Layout> Pop<T, R, L> {
+    /// Returns the position of the n-th 1 in `r`, counting from the start of `r`.
+    /// Same as [`RankSelect::select_one_in`].
+    ///
+    /// Computed by `rank1` and `select1` on the auxiliary data,
+    /// so that a cursor far from the start of the bits costs the same as one near it.
+    ///
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_aux::Pop;
+    /// let mut pop = Pop::<u64>::new(1 << 20);
+    /// for i in [5, 100, 70_000, 500_000, 500_001] {
+    ///     pop.set1(i);
+    /// }
+    /// assert_eq!(pop.select1_in(100..500_001, 1), Some(70_000));
+    /// assert_eq!(pop.select1_in(100..500_001, 3), None);
+    /// assert_eq!(pop.select1_after(70_001, 1), Some(500_001));
+    /// assert_eq!(pop.select1_after(500_002, 0), None);
+    /// assert_eq!(pop.select0_in(100.., 5), Some(106));
+    /// assert_eq!(pop.select0_in(500_000..500_002, 0), None);
+    /// ```
+    #[inline]
+    pub fn select1_in<Idx: RangeBounds<usize>>(&self, r: Idx, n: usize) -> Option<usize> {
+        RankSelect::select_one_in(self, r, n)
+    }
+
+    /// Returns the position of the n-th 0 in `r`, counting from the start of `r`.
+    #[inline]
+    pub fn select0_in<Idx: RangeBounds<usize>>(&self, r: Idx, n: usize) -> Option<usize> {
+        RankSelect::select_zero_in(self, r, n)
+    }
+
+    /// Returns the position of the n-th 1 at or after `i`.
+    #[inline]
+    pub fn select1_after(&self, i: usize, n: usize) -> Option<usize> {
+        RankSelect::select_one_after(self, i, n)
+    }
+}
+
+impl<T: Block, R: Deref<Target = Bits<T>>, L> BitLen for Pop<T, R, L> {
+    #[inline]
+    fn bits(&self) -> usize {
+        self.bits()
+    }
+    #[inline]
+    fn bit(&self, i: usize) -> Option<bool> {
+        self.test(i)
+    }
+}
+
+impl<T: Block + Select, R: Deref<Target = Bits<T>>, L: Layout> RankSelect for Pop<T, R, L> {
+    #[inline]
+    fn ones(&self) -> usize {
+        self.count1()
+    }
+    #[inline]
+    fn rank_ones<Idx: RangeBounds<usize>>(&self, r: Idx) -> usize {
+        self.rank1(r)
+    }
+    #[inline]
+    fn select_one(&self, n: usize) -> Option<usize> {
+        self.select1(n)
+    }
+    #[inline]
+    fn select_zero(&self, n: usize) -> Option<usize> {
+        self.select0(n)
     }
 }
 
@@ -305,7 +510,7 @@ where
     p2
 }
 
-impl<T: Block + BlockMut> Pop<T> {
+impl<T: Block + BlockMut, R: DerefMut<Target = Bits<T>>, L> Pop<T, R, L> {
     /// Swaps a bit at `i` by `bit` and returns the previous value.
     fn swap(&mut self, i: usize, bit: bool) -> bool {
         let before = self.repr.test(i);
@@ -318,11 +523,16 @@ impl<T: Block + BlockMut> Pop<T> {
     }
 }
 
-impl<T: Block + BlockMut> Pop<T> {
+impl<T: Block + BlockMut, R: DerefMut<Target = Bits<T>>, L: Layout> Pop<T, R, L> {
+    /// Enables the bit at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds, or if the counts overflow `L`.
     #[inline]
     pub fn set1(&mut self, index: usize) {
-        if !self.swap(index, true) {
-            self.aux.incr(index, 1);
+        if let Err(err) = self.try_set1(index) {
+            panic!("set1({index}): {err}");
         }
     }
 
@@ -332,24 +542,45 @@ impl<T: Block + BlockMut> Pop<T> {
             self.aux.decr(index, 1);
         }
     }
+
+    /// Enables the bit at `index`, or returns an error if `index` is out of bounds,
+    /// or [`BitsError::Overflow`] if the counts do not fit in `L` after the update.
+    /// The bits are left unchanged on errors.
+    #[inline]
+    pub fn try_set1(&mut self, index: usize) -> Result<(), BitsError> {
+        BitsError::check_index(index, self.bits())?;
+        if !self.test(index).unwrap_or(false) {
+            self.aux.incr(index, 1)?;
+            self.swap(index, true);
+        }
+        Ok(())
+    }
+
+    /// Disables the bit at `index`, or returns an error if `index` is out of bounds.
+    #[inline]
+    pub fn try_set0(&mut self, index: usize) -> Result<(), BitsError> {
+        BitsError::check_index(index, self.bits())?;
+        self.set0(index);
+        Ok(())
+    }
 }
 
-impl Aux {
-    fn new(n: usize) -> Aux {
+impl<L: Layout> Aux<L> {
+    fn new(n: usize) -> Aux<L> {
         let ubs = vec![0; ubs_len(n)];
-        let lbs = vec![l1l2::L1L2::zero(); lbs_len(n)];
+        let lbs = vec![L::zero(); lbs_len(n)];
         Aux { ubs, lbs }
     }
 
     #[inline]
-    fn lb(&self, i: usize) -> &[l1l2::L1L2] {
+    fn lb(&self, i: usize) -> &[L] {
         let s = (MAX_SB_LEN + 1) * i;
         let e = cmp::min(s + (MAX_SB_LEN + 1), self.lbs.len());
         &self.lbs[s..e]
     }
 
     #[inline]
-    fn lb_mut(&mut self, i: usize) -> &mut [l1l2::L1L2] {
+    fn lb_mut(&mut self, i: usize) -> &mut [L] {
         let s = (MAX_SB_LEN + 1) * i;
         let e = cmp::min(s + (MAX_SB_LEN + 1), self.lbs.len());
         &mut self.lbs[s..e]
@@ -367,27 +598,37 @@ impl Aux {
         bit::blocks(self.lbs.len(), MAX_SB_LEN + 1)
     }
 
-    fn incr(&mut self, p0: usize, delta: u64) {
+    /// Adds `delta` to the counts of the blocks including `p0`,
+    /// or returns [`BitsError::Overflow`] without any changes if they do not fit in `L`.
+    fn incr(&mut self, p0: usize, delta: u64) -> Result<(), BitsError> {
         use fenwicktree::Incr;
 
         let (q0, r0) = (p0 / UPPER_BLOCK, p0 % UPPER_BLOCK);
         let (q1, r1) = (r0 / SUPER_BLOCK, r0 % SUPER_BLOCK);
 
+        // No L1 value exceeds the count of the upper block, so checking it is enough.
+        let (s, e): (u64, u64) = (self.ubs.sum(q0), self.ubs.sum(q0 + 1));
+        let count = e - s;
+        if L::L1_MAX - count < delta {
+            return Err(BitsError::Overflow);
+        }
+
         self.ubs.incr(q0 + 1, delta);
 
         let lo = self.lb_mut(q0);
-        lo.incr(q1 + 1, delta);
+        L::incr(lo, q1 + 1, delta);
 
         // Update L2 array which is interleaved into L1
         let sb = q1 + 1; // +1 because fenwick doesn't use index 0
         let bb = r1 / BASIC_BLOCK + 1; // +1 to skip index 0 which is for L1
         if bb < l1l2::LEN {
             lo[sb] = {
-                let mut arr = l1l2::L1L2::split(lo[sb]);
+                let mut arr = lo[sb].split();
                 arr[bb] += delta;
-                l1l2::L1L2::merge(arr)
+                L::merge(arr)
             };
         }
+        Ok(())
     }
 
     fn decr(&mut self, p0: usize, delta: u64) {
@@ -400,15 +641,15 @@ impl Aux {
         hi.decr(q0 + 1, delta);
 
         let lo = self.lb_mut(q0);
-        lo.decr(q1 + 1, delta);
+        L::decr(lo, q1 + 1, delta);
 
         let sb = q1 + 1;
         let bb = r1 / BASIC_BLOCK + 1;
         if bb < l1l2::LEN {
             lo[sb] = {
-                let mut arr = l1l2::L1L2::split(lo[sb]);
+                let mut arr = lo[sb].split();
                 arr[bb] -= delta;
-                l1l2::L1L2::merge(arr)
+                L::merge(arr)
             };
         }
     }
@@ -471,3 +712,31 @@ impl Aux {
 //         Rho(Imp { buckets: buckets.into(), samples: None, bit_vec: dat.into() })
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Counts an upper block as almost full without allocating `2^32` bits.
+    fn almost_full<L: Layout>() -> Pop<u64, BitVec<u64>, L> {
+        let mut pop = Pop::<u64, BitVec<u64>, L>::new(4096);
+        pop.aux.ubs[1] = L1L2::L1_MAX - 1;
+        pop
+    }
+
+    #[test]
+    fn incr_overflow() {
+        let mut pop = almost_full::<L1L2>();
+        assert_eq!(pop.try_set1(10), Ok(()));
+        let aux = pop.aux.clone();
+        assert_eq!(pop.try_set1(11), Err(BitsError::Overflow));
+        assert_eq!(pop.test(11), Some(false));
+        assert_eq!(pop.aux, aux);
+        assert_eq!(pop.try_set1(10), Ok(()), "setting a bit already set does not count");
+
+        let mut wide = almost_full::<WideL1L2>();
+        assert_eq!(wide.try_set1(10), Ok(()));
+        assert_eq!(wide.try_set1(11), Ok(()));
+        assert_eq!(wide.aux.ubs[1], L1L2::L1_MAX + 1);
+    }
+}
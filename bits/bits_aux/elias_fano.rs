@@ -0,0 +1,209 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::ops::{Range, RangeBounds};
+
+use bits_core::block::*;
+use bits_core::{BitLen, Bits, RankSelect, SpaceUsage};
+
+use crate::Rank9;
+
+/// `EliasFano` is a static rank/select dictionary for sparse bitmaps.
+///
+/// Only the positions of ones are stored, each split into the lower `l` bits packed as is
+/// and the upper bits stored in unary in a [`Rank9`], where `l = log2(bits / ones)`.
+/// It takes about `2 + l` bits per one, instead of a bit per position for [`crate::Pop`].
+///
+/// # Examples
+///
+/// ```
+/// # use bits_aux::EliasFano;
+/// let ef = EliasFano::new(1_000_000, &[3, 40, 41, 999_999]);
+/// assert_eq!(ef.bits(), 1_000_000);
+/// assert_eq!(ef.count1(), 4);
+/// assert_eq!(ef.test(40), Some(true));
+/// assert_eq!(ef.test(42), Some(false));
+/// assert_eq!(ef.rank1(..41), 2);
+/// assert_eq!(ef.rank0(..41), 39);
+/// assert_eq!(ef.select1(3), Some(999_999));
+/// assert_eq!(ef.select0(3), Some(4));
+/// assert_eq!(ef.select0(39), Some(42));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EliasFano {
+    bits: usize,
+    ones: usize,
+    // The number of lower bits.
+    l: usize,
+    lows: Vec<u64>,
+    highs: Rank9,
+}
+
+impl EliasFano {
+    /// Constructs a dictionary of `bits` bits, in which `ones` are set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ones` is not strictly increasing, or has a position not less than `bits`.
+    pub fn new(bits: usize, ones: &[usize]) -> EliasFano {
+        assert!(ones.windows(2).all(|w| w[0] < w[1]), "ones must be strictly increasing");
+        assert!(ones.iter().all(|&p| p < bits), "ones must be less than {bits}");
+
+        let m = ones.len();
+        let l = if m == 0 || bits <= m { 0 } else { (bits / m).ilog2() as usize };
+
+        let mut lows = vec![0; bit::blocks(m * l, 64)];
+        let mut highs = vec![0u64; bit::blocks(m + (bits >> l) + 1, 64)];
+        for (i, &p) in ones.iter().enumerate() {
+            if l > 0 {
+                Bits::new_mut(&mut lows).pack(i * l, l, p as u64);
+            }
+            Bits::new_mut(&mut highs).set1((p >> l) + i);
+        }
+
+        EliasFano { bits, ones: m, l, lows, highs: Rank9::from(highs) }
+    }
+
+    #[inline]
+    pub fn bits(&self) -> usize {
+        self.bits
+    }
+
+    #[inline]
+    pub fn count1(&self) -> usize {
+        self.ones
+    }
+
+    #[inline]
+    pub fn count0(&self) -> usize {
+        self.bits - self.ones
+    }
+
+    #[inline]
+    fn low(&self, i: usize) -> usize {
+        if self.l == 0 {
+            0
+        } else {
+            Bits::new(&self.lows).unpack::<u64>(i * self.l, self.l) as usize
+        }
+    }
+
+    #[inline]
+    pub fn test(&self, i: usize) -> Option<bool> {
+        (i < self.bits).then(|| self.search(i).is_ok())
+    }
+
+    /// Searches `p` in the ones, which must be less than `bits`.
+    /// Returns `Ok` with the index of the one at `p` if found,
+    /// otherwise `Err` with the number of ones before `p`.
+    fn search(&self, p: usize) -> Result<usize, usize> {
+        let h = p >> self.l;
+        let low = p & ((1 << self.l) - 1);
+        // Ones in the bucket `h` are at `h + i` in `highs` for `i` in `lo..hi`,
+        // where the lower bits are sorted.
+        let mut lo = if h == 0 { 0 } else { self.highs.select0(h - 1).expect("bucket must exist") + 1 - h };
+        let mut hi = self.highs.select0(h).expect("bucket must exist") - h;
+        while lo < hi {
+            let k = lo + (hi - lo) / 2;
+            match self.low(k).cmp(&low) {
+                Ordering::Less => lo = k + 1,
+                Ordering::Equal => return Ok(k),
+                Ordering::Greater => hi = k,
+            }
+        }
+        Err(lo)
+    }
+
+    /// Counts ones before `p`.
+    #[inline]
+    fn rank1_to(&self, p: usize) -> usize {
+        if p >= self.bits {
+            return self.ones;
+        }
+        match self.search(p) {
+            Ok(i) | Err(i) => i,
+        }
+    }
+
+    #[inline]
+    pub fn rank1<R: RangeBounds<usize>>(&self, r: R) -> usize {
+        let Range { start, end } = bit::bounded(&r, 0, self.bits);
+        self.rank1_to(end) - self.rank1_to(start)
+    }
+
+    #[inline]
+    pub fn rank0<R: RangeBounds<usize>>(&self, r: R) -> usize {
+        let r = bit::bounded(&r, 0, self.bits);
+        r.len() - self.rank1(r)
+    }
+
+    pub fn select1(&self, n: usize) -> Option<usize> {
+        (n < self.ones).then(|| {
+            let high = self.highs.select1(n).expect("select1 must be ok") - n;
+            high << self.l | self.low(n)
+        })
+    }
+
+    pub fn select0(&self, n: usize) -> Option<usize> {
+        (n < self.count0()).then(|| {
+            // The number of ones which have at most `n` zeros before them.
+            let (mut lo, mut hi) = (0, self.ones);
+            while lo < hi {
+                let k = lo + (hi - lo) / 2;
+                if self.select1(k).expect("select1 must be ok") - k <= n {
+                    lo = k + 1;
+                } else {
+                    hi = k;
+                }
+            }
+            n + lo
+        })
+    }
+}
+
+impl<T: Block + Count> From<&Bits<T>> for EliasFano {
+    fn from(bits: &Bits<T>) -> Self {
+        let mut ones = Vec::new();
+        for (q, b) in bits.as_slice().iter().enumerate().filter(|(_, b)| b.any()) {
+            ones.extend((0..T::BITS).filter(|&i| b.test(i) == Some(true)).map(|i| q * T::BITS + i));
+        }
+        EliasFano::new(bits.bits(), &ones)
+    }
+}
+
+impl SpaceUsage for EliasFano {
+    #[inline]
+    fn heap_bytes(&self) -> usize {
+        self.lows.heap_bytes() + self.highs.heap_bytes()
+    }
+}
+
+impl BitLen for EliasFano {
+    #[inline]
+    fn bits(&self) -> usize {
+        self.bits()
+    }
+    #[inline]
+    fn bit(&self, i: usize) -> Option<bool> {
+        self.test(i)
+    }
+}
+
+impl RankSelect for EliasFano {
+    #[inline]
+    fn ones(&self) -> usize {
+        self.count1()
+    }
+    #[inline]
+    fn rank_ones<R: RangeBounds<usize>>(&self, r: R) -> usize {
+        self.rank1(r)
+    }
+    #[inline]
+    fn select_one(&self, n: usize) -> Option<usize> {
+        self.select1(n)
+    }
+    #[inline]
+    fn select_zero(&self, n: usize) -> Option<usize> {
+        self.select0(n)
+    }
+}
@@ -1,23 +1,75 @@
-use std::cmp::Ordering;
-use std::fmt::{self, Debug, Formatter};
-use std::iter::Sum;
-use std::ops::{Add, AddAssign, Sub, SubAssign};
+use core::cmp::Ordering;
+use core::fmt::{self, Debug, Formatter};
+use core::iter::Sum;
+use core::ops::{Add, AddAssign, Sub, SubAssign};
 
+use bits_core::SpaceUsage;
+use fenwicktree::{Decr, Incr, Prefix};
+
+pub(crate) const LEN: usize = 4;
+
+/// The layout of an entry in the lower directory of [`Pop`](crate::Pop).
+///
+/// An entry holds a L1 value, a node of the fenwick tree over super blocks in an upper block,
+/// and L2 values, the counts of the first three basic blocks in the super block.
+/// A L1 value is at most the number of ones in the upper block, which has `2^32` bits.
+///
+/// `Pop` checks that L1 values fit in [`Layout::L1_MAX`] before an update,
+/// and reports [`BitsError::Overflow`](bits_core::BitsError::Overflow) instead of corrupting entries.
+pub trait Layout: private::Entry + Copy + Default + Debug + Eq + SpaceUsage {
+    /// The largest L1 value an entry can hold.
+    const L1_MAX: u64;
+}
+
+/// Packs a 32-bit L1 and three 10-bit L2 into a `u64`.
+///
+/// An upper block in which all of `2^32` bits are set does not fit.
 #[derive(Copy, Clone, Default, PartialEq, Eq)]
-pub(crate) struct L1L2(u64);
+pub struct L1L2(u64);
 
-impl Debug for L1L2 {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        f.debug_tuple("L1L2")
-            .field(&self.l1())
-            .field(&self.l2::<0>())
-            .field(&self.l2::<1>())
-            .field(&self.l2::<2>())
-            .finish()
-    }
+/// Stores a 64-bit L1 and three 16-bit L2 separately.
+///
+/// It takes twice the space of [`L1L2`], but every upper block fits.
+#[derive(Copy, Clone, Default, PartialEq, Eq)]
+pub struct WideL1L2 {
+    l1: u64,
+    l2: [u16; LEN - 1],
 }
 
-pub(crate) const LEN: usize = 4;
+mod private {
+    /// Operations on entries, and on a fenwick tree of entries.
+    pub trait Entry: Sized {
+        fn zero() -> Self;
+
+        /// Packs `[L1, L2, L2, L2]`, which must fit in the entry.
+        fn merge(arr: [u64; super::LEN]) -> Self;
+
+        fn split(self) -> [u64; super::LEN];
+
+        fn l1(self) -> u64;
+
+        /// Sum of basic blocks. [0,i)
+        #[inline]
+        fn l2_sum(self, i: usize) -> u64 {
+            assert!(i < super::LEN, "basic block: index out of bounds");
+            self.split()[1..=i].iter().sum()
+        }
+
+        fn build(lb: &mut [Self]);
+
+        fn sum(lb: &[Self], i: usize) -> u64;
+
+        /// Finds the super block in which the `r`-th one is, and subtracts ones before it from `r`.
+        fn find1(lb: &[Self], r: &mut u64) -> usize;
+
+        /// Finds the super block in which the `r`-th zero is, and subtracts zeros before it from `r`.
+        fn find0(lb: &[Self], r: &mut u64) -> usize;
+
+        fn incr(lb: &mut [Self], i: usize, delta: u64);
+
+        fn decr(lb: &mut [Self], i: usize, delta: u64);
+    }
+}
 
 const L1_MASK: u64 = 0x_FFFF_FFFF; // lowest 32 bits
 const L2_MASK: u64 = 0x_03FF; // lowest 10 bits
@@ -27,154 +79,273 @@ const L2_1_SHIFT: u64 = 42;
 const L2_2_SHIFT: u64 = 52;
 const L2_SHIFT: [u64; LEN - 1] = [L2_0_SHIFT, L2_1_SHIFT, L2_2_SHIFT];
 
-impl L1L2 {
-    #[inline]
-    pub(crate) const fn zero() -> Self {
-        Self(0)
-    }
-
-    #[inline]
-    pub(crate) const fn merge(mut arr: [u64; LEN]) -> Self {
-        debug_assert!(arr[0] < L1_MASK && arr[1] < 1024 && arr[2] < 1024 && arr[3] < 1024);
+impl Layout for L1L2 {
+    const L1_MAX: u64 = L1_MASK;
+}
 
-        arr[0] |= arr[1] << L2_SHIFT[0];
-        arr[0] |= arr[2] << L2_SHIFT[1];
-        arr[0] |= arr[3] << L2_SHIFT[2];
-        L1L2(arr[0])
-    }
+impl Layout for WideL1L2 {
+    const L1_MAX: u64 = u64::MAX;
+}
 
+impl L1L2 {
     #[inline]
-    pub(crate) const fn split(ll: Self) -> [u64; LEN] {
-        [ll.l1(), ll.l2::<0>(), ll.l2::<1>(), ll.l2::<2>()]
+    const fn l1(self) -> u64 {
+        self.0 & L1_MASK
     }
 
     #[inline]
-    pub(crate) const fn l1(self) -> u64 {
-        let L1L2(l1l2) = self;
-        l1l2 & L1_MASK
+    const fn l2<const N: usize>(self) -> u64 {
+        (self.0 >> L2_SHIFT[N]) & L2_MASK
     }
 
     #[inline]
-    pub(crate) const fn l2<const N: usize>(self) -> u64 {
-        let L1L2(l1l2) = self;
-        (l1l2 >> L2_SHIFT[N]) & L2_MASK
+    fn add_l1(&mut self, delta: u64) {
+        debug_assert!(self.l1() + delta <= L1_MASK, "L1 overflows into L2");
+        self.0 += delta;
     }
 
-    // Sum of basic blocks. [0,i)
     #[inline]
-    pub(crate) fn l2_sum(self, i: usize) -> u64 {
-        match i {
-            0 => 0,
-            1 => self.l2::<0>(),
-            2 => self.l2::<0>() + self.l2::<1>(),
-            3 => self.l2::<0>() + self.l2::<1>() + self.l2::<2>(),
-            _ => unreachable!("basic block: index out of bounds"),
-        }
+    fn sub_l1(&mut self, delta: u64) {
+        debug_assert!(self.l1() >= delta, "L1 underflows");
+        self.0 -= delta;
     }
 }
 
-// for lower_bound
-impl PartialEq<u64> for L1L2 {
+impl WideL1L2 {
     #[inline]
-    fn eq(&self, u: &u64) -> bool {
-        self.l1().eq(u)
+    const fn l1(self) -> u64 {
+        self.l1
     }
-}
-impl PartialOrd<u64> for L1L2 {
+
     #[inline]
-    fn partial_cmp(&self, u: &u64) -> Option<Ordering> {
-        self.l1().partial_cmp(u)
+    fn add_l1(&mut self, delta: u64) {
+        self.l1 += delta;
     }
-}
 
-// for init
-impl AddAssign<L1L2> for L1L2 {
     #[inline]
-    fn add_assign(&mut self, delta: L1L2) {
-        self.0 += delta.l1();
+    fn sub_l1(&mut self, delta: u64) {
+        self.l1 -= delta;
     }
 }
-// not used for now but here to keep symmetric
-impl SubAssign<L1L2> for L1L2 {
-    #[inline]
-    fn sub_assign(&mut self, delta: L1L2) {
-        self.0 -= delta.l1();
-    }
+
+macro_rules! impl_fenwick {
+    () => {
+        #[inline]
+        fn build(lb: &mut [Self]) {
+            fenwicktree::build(lb);
+        }
+
+        #[inline]
+        fn sum(lb: &[Self], i: usize) -> u64 {
+            lb.sum(i)
+        }
+
+        #[inline]
+        fn find1(lb: &[Self], r: &mut u64) -> usize {
+            crate::find_l1(lb, r)
+        }
+
+        #[inline]
+        fn find0(lb: &[Self], r: &mut u64) -> usize {
+            crate::find_l1(&fenwicktree::complement(lb, crate::SUPER_BLOCK as u64), r)
+        }
+
+        #[inline]
+        fn incr(lb: &mut [Self], i: usize, delta: u64) {
+            lb.incr(i, delta);
+        }
+
+        #[inline]
+        fn decr(lb: &mut [Self], i: usize, delta: u64) {
+            lb.decr(i, delta);
+        }
+    };
 }
 
-// for add
-impl AddAssign<u64> for L1L2 {
+impl private::Entry for L1L2 {
     #[inline]
-    fn add_assign(&mut self, delta: u64) {
-        self.0 += delta;
+    fn zero() -> Self {
+        L1L2(0)
     }
-}
-// for sub
-impl SubAssign<u64> for L1L2 {
+
     #[inline]
-    fn sub_assign(&mut self, delta: u64) {
-        self.0 -= delta;
+    fn merge(arr: [u64; LEN]) -> Self {
+        assert!(arr[0] <= L1_MASK && arr[1..].iter().all(|&l2| l2 <= L2_MASK), "{arr:?} does not fit in L1L2");
+        L1L2(arr[0] | arr[1] << L2_SHIFT[0] | arr[2] << L2_SHIFT[1] | arr[3] << L2_SHIFT[2])
     }
-}
 
-// lower_bound
-impl From<L1L2> for u64 {
     #[inline]
-    fn from(ll: L1L2) -> u64 {
-        ll.l1()
+    fn split(self) -> [u64; LEN] {
+        [self.l1(), self.l2::<0>(), self.l2::<1>(), self.l2::<2>()]
     }
-}
 
-// for accum
-impl Add<u64> for L1L2 {
-    type Output = u64;
     #[inline]
-    fn add(mut self, delta: u64) -> Self::Output {
-        self += delta;
+    fn l1(self) -> u64 {
         self.l1()
     }
-}
-impl Sub<u64> for L1L2 {
-    type Output = u64;
+
+    // Avoids splitting for the most frequent call from `rank1`.
     #[inline]
-    fn sub(mut self, delta: u64) -> Self::Output {
-        self -= delta;
-        self.l1()
+    fn l2_sum(self, i: usize) -> u64 {
+        match i {
+            0 => 0,
+            1 => self.l2::<0>(),
+            2 => self.l2::<0>() + self.l2::<1>(),
+            3 => self.l2::<0>() + self.l2::<1>() + self.l2::<2>(),
+            _ => unreachable!("basic block: index out of bounds"),
+        }
     }
+
+    impl_fenwick!();
 }
 
-impl Sum<L1L2> for u64 {
+impl private::Entry for WideL1L2 {
     #[inline]
-    fn sum<I: Iterator<Item = L1L2>>(iter: I) -> Self {
-        iter.map(|l1l2| l1l2.l1()).sum()
+    fn zero() -> Self {
+        WideL1L2::default()
     }
-}
 
-// not used for now but here to keep symmetric
-impl Add<L1L2> for u64 {
-    type Output = u64;
     #[inline]
-    fn add(self, delta: L1L2) -> Self::Output {
-        self + delta.l1()
+    fn merge(arr: [u64; LEN]) -> Self {
+        let l2 = |i: usize| u16::try_from(arr[i]).unwrap_or_else(|_| panic!("{arr:?} does not fit in WideL1L2"));
+        WideL1L2 { l1: arr[0], l2: [l2(1), l2(2), l2(3)] }
     }
-}
-impl AddAssign<L1L2> for u64 {
+
     #[inline]
-    fn add_assign(&mut self, delta: L1L2) {
-        *self += delta.l1();
+    fn split(self) -> [u64; LEN] {
+        let [a, b, c] = self.l2;
+        [self.l1, a as u64, b as u64, c as u64]
     }
-}
 
-impl Sub<L1L2> for u64 {
-    type Output = u64;
     #[inline]
-    fn sub(self, delta: L1L2) -> Self::Output {
-        self - delta.l1()
+    fn l1(self) -> u64 {
+        self.l1
     }
+
+    impl_fenwick!();
 }
-impl SubAssign<L1L2> for u64 {
-    #[inline]
-    fn sub_assign(&mut self, delta: L1L2) {
-        *self -= delta.l1();
-    }
+
+macro_rules! impls_for_entry {
+    ($( $Entry:ty )*) => ($(
+        impl Debug for $Entry {
+            fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                let [l1, a, b, c] = private::Entry::split(*self);
+                f.debug_tuple(stringify!($Entry)).field(&l1).field(&a).field(&b).field(&c).finish()
+            }
+        }
+
+        impl SpaceUsage for $Entry {
+            #[inline]
+            fn heap_bytes(&self) -> usize {
+                0
+            }
+        }
+
+        // for lower_bound
+        impl PartialEq<u64> for $Entry {
+            #[inline]
+            fn eq(&self, u: &u64) -> bool {
+                self.l1().eq(u)
+            }
+        }
+        impl PartialOrd<u64> for $Entry {
+            #[inline]
+            fn partial_cmp(&self, u: &u64) -> Option<Ordering> {
+                self.l1().partial_cmp(u)
+            }
+        }
+
+        // for init
+        impl AddAssign<$Entry> for $Entry {
+            #[inline]
+            fn add_assign(&mut self, delta: $Entry) {
+                self.add_l1(delta.l1());
+            }
+        }
+        // not used for now but here to keep symmetric
+        impl SubAssign<$Entry> for $Entry {
+            #[inline]
+            fn sub_assign(&mut self, delta: $Entry) {
+                self.sub_l1(delta.l1());
+            }
+        }
+
+        // for add
+        impl AddAssign<u64> for $Entry {
+            #[inline]
+            fn add_assign(&mut self, delta: u64) {
+                self.add_l1(delta);
+            }
+        }
+        // for sub
+        impl SubAssign<u64> for $Entry {
+            #[inline]
+            fn sub_assign(&mut self, delta: u64) {
+                self.sub_l1(delta);
+            }
+        }
+
+        // lower_bound
+        impl From<$Entry> for u64 {
+            #[inline]
+            fn from(ll: $Entry) -> u64 {
+                ll.l1()
+            }
+        }
+
+        // for accum
+        impl Add<u64> for $Entry {
+            type Output = u64;
+            #[inline]
+            fn add(mut self, delta: u64) -> Self::Output {
+                self += delta;
+                self.l1()
+            }
+        }
+        impl Sub<u64> for $Entry {
+            type Output = u64;
+            #[inline]
+            fn sub(mut self, delta: u64) -> Self::Output {
+                self -= delta;
+                self.l1()
+            }
+        }
+
+        impl Sum<$Entry> for u64 {
+            #[inline]
+            fn sum<I: Iterator<Item = $Entry>>(iter: I) -> Self {
+                iter.map(|ll| ll.l1()).sum()
+            }
+        }
+
+        // not used for now but here to keep symmetric
+        impl Add<$Entry> for u64 {
+            type Output = u64;
+            #[inline]
+            fn add(self, delta: $Entry) -> Self::Output {
+                self + delta.l1()
+            }
+        }
+        impl AddAssign<$Entry> for u64 {
+            #[inline]
+            fn add_assign(&mut self, delta: $Entry) {
+                *self += delta.l1();
+            }
+        }
+
+        impl Sub<$Entry> for u64 {
+            type Output = u64;
+            #[inline]
+            fn sub(self, delta: $Entry) -> Self::Output {
+                self - delta.l1()
+            }
+        }
+        impl SubAssign<$Entry> for u64 {
+            #[inline]
+            fn sub_assign(&mut self, delta: $Entry) {
+                *self -= delta.l1();
+            }
+        }
+    )*)
 }
+impls_for_entry!(L1L2 WideL1L2);
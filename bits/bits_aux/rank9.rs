@@ -0,0 +1,217 @@
+use alloc::vec::Vec;
+use core::ops::{Range, RangeBounds};
+
+use bits_core::block::*;
+use bits_core::{BitLen, Bits, BitsError, RankSelect, SpaceUsage};
+
+/// `Rank9` is a static alternative to [`crate::Pop`] for read-only bitmaps.
+///
+/// Bits are stored in 512-bit lines, and each line inlines its counts next to the data
+/// (Vigna's rank9 layout), so that `rank1` touches a single line and needs no fenwick tree sums.
+/// It costs 25% of extra space, and can not be updated after construction.
+///
+/// # Examples
+///
+/// ```
+/// # use bits_aux::Rank9;
+/// let r9 = Rank9::from(vec![0b1010u64, 0, !0]);
+/// assert_eq!(r9.bits(), 192);
+/// assert_eq!(r9.count1(), 66);
+/// assert_eq!(r9.rank1(..128), 2);
+/// assert_eq!(r9.rank1(3..130), 3);
+/// assert_eq!(r9.select1(1), Some(3));
+/// assert_eq!(r9.select1(2), Some(128));
+/// assert_eq!(r9.select0(2), Some(4));
+/// assert_eq!(r9.select0(126), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rank9 {
+    lines: Vec<Line>,
+    bits: usize,
+    ones: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[repr(C)]
+struct Line {
+    // The number of ones before this line.
+    abs: u64,
+    // The number of ones in words[..k] for k in 1..8, 9 bits for each.
+    rel: u64,
+    words: [u64; WORDS],
+}
+
+const WORDS: usize = 8;
+const LINE: usize = 64 * WORDS;
+
+impl Line {
+    #[inline]
+    fn rel(&self, k: usize) -> usize {
+        if k == 0 {
+            0
+        } else {
+            (self.rel >> (9 * (k - 1)) & 0x1FF) as usize
+        }
+    }
+}
+
+impl SpaceUsage for Rank9 {
+    #[inline]
+    fn heap_bytes(&self) -> usize {
+        self.lines.capacity() * core::mem::size_of::<Line>()
+    }
+}
+
+impl From<Vec<u64>> for Rank9 {
+    fn from(words: Vec<u64>) -> Self {
+        Rank9::from(Bits::new(words.as_slice()))
+    }
+}
+
+impl From<&Bits<u64>> for Rank9 {
+    fn from(bits: &Bits<u64>) -> Self {
+        let mut abs = 0;
+        let lines = bits
+            .as_slice()
+            .chunks(WORDS)
+            .map(|chunk| {
+                let mut words = [0; WORDS];
+                words[..chunk.len()].copy_from_slice(chunk);
+
+                let (mut rel, mut sum) = (0, 0);
+                for (k, w) in words.iter().enumerate().take(WORDS - 1) {
+                    sum += w.count_ones() as u64;
+                    rel |= sum << (9 * k);
+                }
+                let line = Line { abs, rel, words };
+                abs += sum + words[WORDS - 1].count_ones() as u64;
+                line
+            })
+            .collect();
+
+        Rank9 { lines, bits: bits.bits(), ones: abs as usize }
+    }
+}
+
+impl Rank9 {
+    #[inline]
+    pub fn bits(&self) -> usize {
+        self.bits
+    }
+
+    #[inline]
+    pub fn test(&self, i: usize) -> Option<bool> {
+        (i < self.bits).then(|| {
+            let (q, r) = bit::addr(i, LINE);
+            self.lines[q].words[r / 64] >> (r % 64) & 1 == 1
+        })
+    }
+
+    #[inline]
+    pub fn count1(&self) -> usize {
+        self.ones
+    }
+
+    #[inline]
+    pub fn count0(&self) -> usize {
+        self.bits - self.ones
+    }
+
+    #[inline]
+    fn rank1_to(&self, p: usize) -> usize {
+        if p == self.bits {
+            return self.ones;
+        }
+        let (q, r) = bit::addr(p, LINE);
+        let line = &self.lines[q];
+        let (k, o) = (r / 64, r % 64);
+        line.abs as usize + line.rel(k) + line.words[k].rank1(..o)
+    }
+
+    #[inline]
+    pub fn rank1<R: RangeBounds<usize>>(&self, r: R) -> usize {
+        let Range { start, end } = bit::bounded(&r, 0, self.bits);
+        self.rank1_to(end) - self.rank1_to(start)
+    }
+
+    #[inline]
+    pub fn rank0<R: RangeBounds<usize>>(&self, r: R) -> usize {
+        let r = bit::bounded(&r, 0, self.bits);
+        r.len() - self.rank1(r)
+    }
+
+    /// Counts occurrences of `1` in `r`, or returns an error if `r` is out of bounds.
+    #[inline]
+    pub fn try_rank1<R: RangeBounds<usize>>(&self, r: R) -> Result<usize, BitsError> {
+        BitsError::check_range(&r, self.bits).map(|r| self.rank1(r))
+    }
+
+    pub fn select1(&self, n: usize) -> Option<usize> {
+        (n < self.ones).then(|| {
+            // The line containing the n-th one is the last one which has `abs <= n`.
+            let q = self.lines.partition_point(|l| l.abs as usize <= n) - 1;
+            let line = &self.lines[q];
+            let r = n - line.abs as usize;
+            let k = (1..WORDS).take_while(|&k| line.rel(k) <= r).last().unwrap_or(0);
+            q * LINE + k * 64 + line.words[k].select1(r - line.rel(k)).expect("select1 must be ok")
+        })
+    }
+
+    pub fn select0(&self, n: usize) -> Option<usize> {
+        (n < self.count0()).then(|| {
+            let zeros = |q: usize, l: &Line| q * LINE - l.abs as usize;
+            let mut q = 0;
+            let (mut lo, mut hi) = (0, self.lines.len());
+            while lo < hi {
+                let m = lo + (hi - lo) / 2;
+                if zeros(m, &self.lines[m]) <= n {
+                    q = m;
+                    lo = m + 1;
+                } else {
+                    hi = m;
+                }
+            }
+            let line = &self.lines[q];
+            let r = n - zeros(q, line);
+            let rel0 = |k: usize| k * 64 - line.rel(k);
+            let k = (1..WORDS).take_while(|&k| rel0(k) <= r).last().unwrap_or(0);
+            q * LINE + k * 64 + line.words[k].select0(r - rel0(k)).expect("select0 must be ok")
+        })
+    }
+
+    /// Returns the position of the n-th 1, or an error if `n` is not less than `self.count1()`.
+    #[inline]
+    pub fn try_select1(&self, n: usize) -> Result<usize, BitsError> {
+        self.select1(n).ok_or(BitsError::OutOfBounds { index: n, len: self.ones })
+    }
+}
+
+impl BitLen for Rank9 {
+    #[inline]
+    fn bits(&self) -> usize {
+        self.bits()
+    }
+    #[inline]
+    fn bit(&self, i: usize) -> Option<bool> {
+        self.test(i)
+    }
+}
+
+impl RankSelect for Rank9 {
+    #[inline]
+    fn ones(&self) -> usize {
+        self.count1()
+    }
+    #[inline]
+    fn rank_ones<R: RangeBounds<usize>>(&self, r: R) -> usize {
+        self.rank1(r)
+    }
+    #[inline]
+    fn select_one(&self, n: usize) -> Option<usize> {
+        self.select1(n)
+    }
+    #[inline]
+    fn select_zero(&self, n: usize) -> Option<usize> {
+        self.select0(n)
+    }
+}
@@ -1,12 +1,39 @@
-use std::ops::RangeBounds;
+use std::ops::{Range, RangeBounds};
 
 use bits_core::block::*;
 use bits_core::mask::helper;
 use bits_core::word::Word;
-use bits_core::Bits;
+use bits_core::{Bits, SpaceUsage};
 
+/// A bits block which allocates `T` lazily.
+///
+/// The allocation also holds an optional cache built by [`Buf::build_cache`]:
+/// the number of enabled bits before every 512 bits, used to answer `rank1` and `select1`
+/// without scanning the buffer from the start. Any mutation drops the cache.
+/// An unallocated `Buf` is a single null pointer.
+///
+/// # Tests
+///
+/// ```
+/// # use bits_buf::Buf;
+/// assert_eq!(std::mem::size_of::<Buf<[u64; 1024]>>(), 8);
+/// ```
 #[derive(Debug, Default, Clone)]
-pub struct Buf<T: private::Array>(Option<Box<T>>);
+pub struct Buf<T: private::Array>(Option<Box<Heap<T>>>);
+
+#[derive(Debug, Clone)]
+struct Heap<T> {
+    bits: T,
+    cache: Option<Box<[u32]>>,
+}
+
+/// A free list of zeroed buffers.
+///
+/// Sparse masks with churn allocate and release buffers repeatedly.
+/// [`Buf::compact_into`] returns a released buffer to the pool instead of deallocating it,
+/// and [`Buf::set1_in`] takes a buffer from the pool before allocating a new one.
+#[derive(Debug, Default)]
+pub struct Pool<T: private::Array>(Vec<Box<Heap<T>>>);
 
 mod private {
     pub trait Array {}
@@ -26,27 +53,227 @@ impl<B: Word, const N: usize> Buf<[B; N]> {
 
     #[inline]
     fn as_slice(&self) -> &[B] {
-        self.0.as_deref().map_or(&[], |a| a.as_slice())
+        self.0.as_deref().map_or(&[], |h| h.bits.as_slice())
     }
 
     #[inline]
     fn as_mut_slice(&mut self) -> &mut [B] {
-        self.0.as_deref_mut().map_or(&mut [], |a| a.as_mut_slice())
+        self.inner_mut().map_or(&mut [], |b| b.as_mut_slice())
     }
 
     #[inline]
     fn inner(&self) -> Option<&Bits<B>> {
-        self.0.as_deref().map(|a| Bits::new(a.as_slice()))
+        self.0.as_deref().map(|h| Bits::new(h.bits.as_slice()))
     }
 
     #[inline]
     fn inner_mut(&mut self) -> Option<&mut Bits<B>> {
-        self.0.as_deref_mut().map(|a| Bits::new_mut(a.as_mut_slice()))
+        self.0.as_deref_mut().map(|h| {
+            h.cache = None;
+            Bits::new_mut(h.bits.as_mut_slice())
+        })
     }
 
     #[inline]
     fn or_empty(&mut self) -> &mut Bits<B> {
-        Bits::new_mut(self.0.get_or_insert_with(|| Box::new([B::empty(); N])).as_mut_slice())
+        let h = self.0.get_or_insert_with(|| Box::new(Heap { bits: [B::empty(); N], cache: None }));
+        h.cache = None;
+        Bits::new_mut(h.bits.as_mut_slice())
+    }
+
+    /// Returns the words and the cache if the cache is built.
+    #[inline]
+    fn cached(&self) -> Option<(&[B], &[u32])> {
+        self.0.as_deref().and_then(|h| h.cache.as_deref().map(|cache| (h.bits.as_slice(), cache)))
+    }
+
+    /// Returns true if the buffer is allocated.
+    #[inline]
+    pub fn is_allocated(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Releases the buffer if no bits are enabled, and returns true if released.
+    ///
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_core::block::*;
+    /// # use bits_buf::Buf;
+    /// let mut b = Buf::<[u64; 8]>::empty();
+    /// b.set1(10);
+    /// assert!(!b.compact());
+    /// b.set0(10);
+    /// assert!(b.is_allocated());
+    /// assert!(b.compact());
+    /// assert!(!b.is_allocated());
+    /// ```
+    #[inline]
+    pub fn compact(&mut self) -> bool {
+        self.take_if_empty().is_some()
+    }
+
+    /// Same as [`Buf::compact`], but returns the released buffer to `pool`.
+    ///
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_core::block::*;
+    /// # use bits_buf::{Buf, Pool};
+    /// let mut pool = Pool::new();
+    /// let mut a = Buf::<[u64; 8]>::empty();
+    /// let mut b = Buf::<[u64; 8]>::empty();
+    ///
+    /// a.set1_in(3, &mut pool);
+    /// a.set0(3);
+    /// assert!(a.compact_into(&mut pool));
+    /// assert_eq!(pool.len(), 1);
+    ///
+    /// b.set1_in(5, &mut pool);
+    /// assert!(pool.is_empty());
+    /// assert_eq!(b.count1(), 1);
+    /// assert_eq!(b.select1(0), Some(5));
+    /// ```
+    #[inline]
+    pub fn compact_into(&mut self, pool: &mut Pool<[B; N]>) -> bool {
+        self.take_if_empty().map(|buf| pool.0.push(buf)).is_some()
+    }
+
+    /// Enables the bit at the given index `i`, taking a buffer from `pool` if not allocated.
+    #[inline]
+    pub fn set1_in(&mut self, i: usize, pool: &mut Pool<[B; N]>) {
+        assert!(i < Self::BITS);
+        if self.0.is_none() {
+            self.0 = pool.0.pop();
+        }
+        self.or_empty().set1(i);
+    }
+
+    fn take_if_empty(&mut self) -> Option<Box<Heap<[B; N]>>> {
+        self.0.take_if(|h| !Bits::new(h.bits.as_slice()).any()).map(|mut h| {
+            h.cache = None;
+            h
+        })
+    }
+
+    /// The number of words sampled by an entry of the cache.
+    #[inline]
+    const fn sample() -> usize {
+        let n = SAMPLE_BITS / B::BITS;
+        if n == 0 {
+            1
+        } else {
+            n
+        }
+    }
+
+    /// Builds the cumulative counts so that `rank1` and `select1` can skip most of the buffer.
+    /// The cache is dropped by `set1`, `set0` and any other mutations.
+    ///
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_core::block::*;
+    /// # use bits_buf::Buf;
+    /// let mut b = Buf::<[u64; 1024]>::empty();
+    /// for i in (0..65536).step_by(3) {
+    ///     b.set1(i);
+    /// }
+    /// b.build_cache();
+    /// assert!(b.is_cached());
+    /// assert_eq!(b.rank1(..65535), 21845);
+    /// assert_eq!(b.rank1(100..60000), 19966);
+    /// assert_eq!(b.select1(21844), Some(65532));
+    /// assert_eq!(b.select0(0), Some(1));
+    /// assert_eq!(b.select0(43689), Some(65534));
+    ///
+    /// b.set0(0);
+    /// assert!(!b.is_cached());
+    /// assert_eq!(b.select1(0), Some(3));
+    /// ```
+    pub fn build_cache(&mut self) {
+        if let Some(h) = self.0.as_deref_mut() {
+            let mut acc = 0;
+            h.cache = Some(
+                h.bits
+                    .chunks(Self::sample())
+                    .map(|ws| {
+                        let c = acc;
+                        acc += Bits::new(ws).count1();
+                        num::cast::<usize, u32>(c).expect("failed to cast from usize to u32")
+                    })
+                    .collect(),
+            );
+        }
+    }
+
+    /// Returns true if the cache is built and valid.
+    #[inline]
+    pub fn is_cached(&self) -> bool {
+        self.cached().is_some()
+    }
+
+    /// Counts occurrences of `1` in `[0, p)` using the cache.
+    fn cached_rank1(words: &[B], cache: &[u32], p: usize) -> usize {
+        let (w, o) = bit::addr(p, B::BITS);
+        let s = (w / Self::sample()).min(cache.len() - 1);
+        cache[s] as usize + Bits::new(&words[s * Self::sample()..w]).count1() + words.get(w).map_or(0, |b| b.rank1(..o))
+    }
+
+    /// Returns the position of the n-th `1`, or the n-th `0` if `ZERO`, using the cache.
+    fn cached_select<const ZERO: bool>(words: &[B], cache: &[u32], mut n: usize) -> Option<usize> {
+        let sample_bits = Self::sample() * B::BITS;
+        let count = |k: usize| if ZERO { k * sample_bits - cache[k] as usize } else { cache[k] as usize };
+
+        // Binary search to find the last sample `s` such that `count(s) <= n`.
+        // `count(0)` is always 0, so `s` exists.
+        let (mut lo, mut hi) = (0, cache.len());
+        while lo < hi {
+            let m = lo + (hi - lo) / 2;
+            if count(m) <= n {
+                lo = m + 1;
+            } else {
+                hi = m;
+            }
+        }
+        let s = lo - 1;
+
+        n -= count(s);
+        for (i, w) in words.iter().enumerate().skip(s * Self::sample()) {
+            let c = if ZERO { w.count0() } else { w.count1() };
+            if n < c {
+                let p = if ZERO { w.select0(n) } else { w.select1(n) };
+                return p.map(|p| i * B::BITS + p);
+            }
+            n -= c;
+        }
+        None
+    }
+}
+
+const SAMPLE_BITS: usize = 512;
+
+impl<T: private::Array> Pool<T> {
+    #[inline]
+    pub fn new() -> Self {
+        Pool(Vec::new())
+    }
+
+    /// Returns the number of buffers in the pool.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Drops all pooled buffers.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.0.clear()
     }
 }
 
@@ -72,7 +299,10 @@ impl<B: Word, const N: usize> Block for Buf<[B; N]> {
 
     #[inline]
     fn test(&self, i: usize) -> Option<bool> {
-        self.inner().and_then(|b| b.test(i))
+        match self.inner() {
+            Some(b) => b.test(i),
+            None => (i < Self::BITS).then_some(false),
+        }
     }
 }
 impl<B: Word, const N: usize> BlockMut for Buf<[B; N]> {
@@ -82,31 +312,57 @@ impl<B: Word, const N: usize> BlockMut for Buf<[B; N]> {
         self.or_empty().set1(i);
     }
 
+    /// Disabling a bit never allocates the buffer.
+    ///
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_core::block::*;
+    /// # use bits_buf::Buf;
+    /// let mut b = Buf::<[u64; 8]>::empty();
+    /// b.set0(10);
+    /// assert!(!b.is_allocated());
+    /// assert_eq!(b.test(10), Some(false));
+    /// ```
     #[inline]
     fn set0(&mut self, i: usize) {
         assert!(i < Self::BITS);
-        self.or_empty().set0(i);
+        if let Some(bits) = self.inner_mut() {
+            bits.set0(i);
+        }
     }
 }
 
 impl<B: Word, const N: usize> Count for Buf<[B; N]> {
     #[inline]
     fn count1(&self) -> usize {
-        self.inner().map_or(0, |b| b.count1())
+        match self.cached() {
+            Some((words, cache)) => Self::cached_rank1(words, cache, Self::BITS),
+            None => self.inner().map_or(0, |b| b.count1()),
+        }
     }
 }
 
 impl<B: Word, const N: usize> Rank for Buf<[B; N]> {
     #[inline]
     fn rank1<R: RangeBounds<usize>>(&self, r: R) -> usize {
-        self.inner().map_or(0, |b| b.rank1(r))
+        match self.cached() {
+            Some((words, cache)) => {
+                let Range { start, end } = bit::bounded(&r, 0, Self::BITS);
+                Self::cached_rank1(words, cache, end) - Self::cached_rank1(words, cache, start)
+            }
+            None => self.inner().map_or(0, |b| b.rank1(r)),
+        }
     }
 }
 
 impl<B: Word, const N: usize> Select for Buf<[B; N]> {
     #[inline]
     fn select1(&self, n: usize) -> Option<usize> {
-        self.inner().and_then(|b| b.select1(n))
+        match self.cached() {
+            Some((words, cache)) => Self::cached_select::<false>(words, cache, n),
+            None => self.inner().and_then(|b| b.select1(n)),
+        }
     }
 
     /// # Tests
@@ -128,14 +384,45 @@ impl<B: Word, const N: usize> Select for Buf<[B; N]> {
     /// ```
     #[inline]
     fn select0(&self, n: usize) -> Option<usize> {
-        match self.inner() {
-            Some(b) => b.select0(n),
+        match (self.cached(), self.inner()) {
+            (Some((words, cache)), _) => Self::cached_select::<true>(words, cache, n),
+            (None, Some(bits)) => bits.select0(n),
             // self.count0() == Self::BITS
-            None => (n < Self::BITS).then_some(n),
+            (None, None) => (n < Self::BITS).then_some(n),
         }
     }
 }
 
+impl<B: Word + SpaceUsage, const N: usize> SpaceUsage for Buf<[B; N]> {
+    /// Counts the buffer only if it is allocated, and the cache if it is built.
+    ///
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_buf::Buf;
+    /// # use bits_core::block::*;
+    /// # use bits_core::SpaceUsage;
+    /// let mut b = Buf::<[u64; 1024]>::empty();
+    /// assert_eq!(b.heap_bytes(), 0);
+    /// b.set1(10);
+    /// let cache_ptr = std::mem::size_of::<Option<Box<[u32]>>>();
+    /// assert_eq!(b.heap_bytes(), 8192 + cache_ptr);
+    /// b.build_cache();
+    /// assert_eq!(b.heap_bytes(), 8192 + cache_ptr + 128 * 4);
+    /// ```
+    #[inline]
+    fn heap_bytes(&self) -> usize {
+        self.0.as_ref().map_or(0, |h| h.heap_bytes())
+    }
+}
+
+impl<T: SpaceUsage> SpaceUsage for Heap<T> {
+    #[inline]
+    fn heap_bytes(&self) -> usize {
+        self.bits.heap_bytes() + self.cache.as_ref().map_or(0, |c| c.heap_bytes())
+    }
+}
+
 impl<B, const N: usize> helper::Assign<Buf<[B; N]>> for Buf<[B; N]>
 where
     B: Word + helper::Assign<B>,
@@ -166,6 +453,7 @@ where
             (Some(a), Some(b)) => helper::Assign::and(a, b),
             (None, _) | (_, None) => a.0 = None,
         };
+        a.compact();
     }
 
     /// # Tests
@@ -185,11 +473,17 @@ where
     ///
     /// Assign::not(&mut a, &b);
     /// assert_eq!(a.as_bits().as_slice(), &[0b_0001, 0, 0, 0]);
+    ///
+    /// // Released when all bits are disabled.
+    /// let c = a.clone();
+    /// Assign::not(&mut a, &c);
+    /// assert!(!a.is_allocated());
     /// ```
     fn not(a: &mut Self, b: &Buf<[B; N]>) {
         if let (Some(a), Some(b)) = (a.inner_mut(), b.inner()) {
             helper::Assign::not(a, b);
         }
+        a.compact();
     }
 
     /// # Tests
@@ -243,6 +537,9 @@ where
     /// let mut c = bits_buf::Buf::<[u64; 4]>::empty();
     /// Assign::xor(&mut c, &a);
     /// assert_eq!(c.as_bits().as_slice(), &[0b_1001, 0, 0, 0]);
+    ///
+    /// Assign::xor(&mut c, &a);
+    /// assert!(!c.is_allocated());
     /// ```
     fn xor(a: &mut Self, b: &Buf<[B; N]>) {
         match (a.inner_mut(), b.inner()) {
@@ -250,5 +547,6 @@ where
             (None, Some(b)) => a.or_empty().copy_from_slice(b),
             _ => {}
         }
+        a.compact();
     }
 }
@@ -0,0 +1,286 @@
+//! `bits_codes`
+//!
+//! Bit-level cursors to write and read variable-length integer codes.
+//! Bits are written from the least significant bit of each word, and
+//! multi-bit fields are stored with their lowest bit first.
+//!
+//! | code         | layout of `n`                                              |
+//! |--------------|------------------------------------------------------------|
+//! | unary        | `n` zeros followed by a one                                |
+//! | gamma        | unary `l = log2(n)`, then the lower `l` bits of `n`        |
+//! | delta        | gamma `l + 1`, then the lower `l` bits of `n`              |
+//! | rice(k)      | unary `n >> k`, then the lower `k` bits of `n`             |
+//!
+//! # Examples
+//!
+//! ```
+//! # use bits_codes::{Reader, Writer};
+//! let gaps = [3, 1, 1, 120, 7];
+//!
+//! let mut w = Writer::<u64>::new();
+//! for &g in &gaps {
+//!     w.write_gamma(g);
+//! }
+//! let bits = w.into_bit_vec();
+//!
+//! let mut r = Reader::new(&bits);
+//! for &g in &gaps {
+//!     assert_eq!(r.read_gamma(), Some(g));
+//! }
+//! ```
+
+use bits_core::word::Word;
+use bits_core::{BitVec, Bits};
+
+/// Appends codes to a growing buffer of `T`.
+#[derive(Debug, Clone, Default)]
+pub struct Writer<T> {
+    data: Vec<T>,
+    pos: usize,
+}
+
+/// Reads codes from `Bits<T>` starting at some position.
+#[derive(Debug, Clone)]
+pub struct Reader<'a, T> {
+    bits: &'a Bits<T>,
+    pos: usize,
+}
+
+#[inline]
+fn log2(n: u64) -> u32 {
+    63 - n.leading_zeros()
+}
+
+impl<T: Word> Writer<T> {
+    #[inline]
+    pub fn new() -> Self {
+        Writer { data: Vec::new(), pos: 0 }
+    }
+
+    /// Constructs a new writer with room for at least `bits` bits.
+    #[inline]
+    pub fn with_capacity(bits: usize) -> Self {
+        Writer { data: Vec::with_capacity(bit::blocks(bits, T::BITS)), pos: 0 }
+    }
+
+    /// Returns the number of bits written so far.
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    #[inline]
+    pub fn as_bits(&self) -> &Bits<T> {
+        Bits::new(&self.data)
+    }
+
+    /// Consumes the writer. Bits after [`Writer::position`] are zero.
+    #[inline]
+    pub fn into_bit_vec(self) -> BitVec<T> {
+        BitVec::from(self.data)
+    }
+
+    #[inline]
+    fn grow(&mut self, bits: usize) {
+        let len = bit::blocks(self.pos + bits, T::BITS);
+        if len > self.data.len() {
+            self.data.resize(len, T::empty());
+        }
+    }
+
+    /// Writes the lower `n` bits of `v`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n > 64`.
+    #[inline]
+    pub fn write_bits(&mut self, v: u64, n: usize) {
+        assert!(n <= 64, "cannot write {n} bits at once");
+        if n > 0 {
+            self.grow(n);
+            Bits::new_mut(&mut self.data).pack(self.pos, n, v);
+            self.pos += n;
+        }
+    }
+
+    /// Writes `n` zeros followed by a one.
+    #[inline]
+    pub fn write_unary(&mut self, n: u64) {
+        let n = n as usize;
+        self.grow(n + 1);
+        self.pos += n;
+        Bits::new_mut(&mut self.data).set1(self.pos);
+        self.pos += 1;
+    }
+
+    /// Writes `n` in Elias gamma code, using `2 * log2(n) + 1` bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n == 0`.
+    #[inline]
+    pub fn write_gamma(&mut self, n: u64) {
+        assert!(n > 0, "gamma code is not defined for 0");
+        let l = log2(n);
+        self.write_unary(l as u64);
+        self.write_bits(n, l as usize);
+    }
+
+    /// Writes `n` in Elias delta code.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n == 0`.
+    ///
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_codes::{Reader, Writer};
+    /// let mut w = Writer::<u8>::new();
+    /// w.write_delta(1);
+    /// w.write_delta(u64::MAX);
+    /// w.write_delta(1000);
+    /// let bits = w.into_bit_vec();
+    ///
+    /// let mut r = Reader::new(&bits);
+    /// assert_eq!(r.read_delta(), Some(1));
+    /// assert_eq!(r.read_delta(), Some(u64::MAX));
+    /// assert_eq!(r.read_delta(), Some(1000));
+    /// ```
+    #[inline]
+    pub fn write_delta(&mut self, n: u64) {
+        assert!(n > 0, "delta code is not defined for 0");
+        let l = log2(n);
+        self.write_gamma(l as u64 + 1);
+        self.write_bits(n, l as usize);
+    }
+
+    /// Writes `n` in Golomb-Rice code with parameter `k`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k > 64`.
+    #[inline]
+    pub fn write_rice(&mut self, n: u64, k: usize) {
+        self.write_unary(n.checked_shr(k as u32).unwrap_or(0));
+        self.write_bits(n, k);
+    }
+}
+
+impl<'a, T: Word> Reader<'a, T> {
+    /// Constructs a reader at the beginning of `bits`.
+    #[inline]
+    pub fn new(bits: &'a Bits<T>) -> Self {
+        Self::at(bits, 0)
+    }
+
+    /// Constructs a reader at `pos`.
+    #[inline]
+    pub fn at(bits: &'a Bits<T>, pos: usize) -> Self {
+        Reader { bits, pos }
+    }
+
+    /// Returns the position of the next bit to read.
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    #[inline]
+    pub fn seek(&mut self, pos: usize) {
+        self.pos = pos;
+    }
+
+    /// Runs `f`, and rewinds the reader if it returns `None`.
+    #[inline]
+    fn rewind_on_none<U>(&mut self, f: impl FnOnce(&mut Self) -> Option<U>) -> Option<U> {
+        let pos = self.pos;
+        let ret = f(self);
+        if ret.is_none() {
+            self.pos = pos;
+        }
+        ret
+    }
+
+    /// Reads `n` bits as the lower bits of `u64`.
+    /// Returns `None` if less than `n` bits remain.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n > 64`.
+    #[inline]
+    pub fn read_bits(&mut self, n: usize) -> Option<u64> {
+        assert!(n <= 64, "cannot read {n} bits at once");
+        (self.pos + n <= self.bits.bits()).then(|| {
+            let v = self.bits.unpack(self.pos, n);
+            self.pos += n;
+            v
+        })
+    }
+
+    /// Reads zeros until a one, and returns the number of zeros.
+    /// Returns `None` if no one remains.
+    #[inline]
+    pub fn read_unary(&mut self) -> Option<u64> {
+        let words = self.bits.as_slice();
+        let (mut q, r) = bit::addr(self.pos, T::BITS);
+        let mut w = *words.get(q)? & (!T::empty() << r);
+        loop {
+            if let Some(i) = w.select1(0) {
+                let one = q * T::BITS + i;
+                let n = one - self.pos;
+                self.pos = one + 1;
+                return Some(n as u64);
+            }
+            q += 1;
+            w = *words.get(q)?;
+        }
+    }
+
+    /// Reads a number written by [`Writer::write_gamma`].
+    #[inline]
+    pub fn read_gamma(&mut self) -> Option<u64> {
+        self.rewind_on_none(|r| {
+            let l = r.read_unary().filter(|&l| l < 64)? as usize;
+            r.read_bits(l).map(|low| 1 << l | low)
+        })
+    }
+
+    /// Reads a number written by [`Writer::write_delta`].
+    #[inline]
+    pub fn read_delta(&mut self) -> Option<u64> {
+        self.rewind_on_none(|r| {
+            let l = r.read_gamma().filter(|&l| l <= 64)? as usize - 1;
+            r.read_bits(l).map(|low| 1 << l | low)
+        })
+    }
+
+    /// Reads a number written by [`Writer::write_rice`] with the same `k`.
+    ///
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_codes::{Reader, Writer};
+    /// let mut w = Writer::<u32>::new();
+    /// w.write_rice(0, 3);
+    /// w.write_rice(42, 3);
+    /// w.write_rice(42, 0);
+    /// let bits = w.into_bit_vec();
+    /// assert_eq!(bits.bits(), 64);
+    ///
+    /// let mut r = Reader::new(&bits);
+    /// assert_eq!(r.read_rice(3), Some(0));
+    /// assert_eq!(r.read_rice(3), Some(42));
+    /// assert_eq!(r.read_rice(0), Some(42));
+    /// assert_eq!(r.read_rice(0), None);
+    /// assert_eq!(r.position(), 4 + 9 + 43);
+    /// ```
+    #[inline]
+    pub fn read_rice(&mut self, k: usize) -> Option<u64> {
+        self.rewind_on_none(|r| {
+            let q = r.read_unary()?;
+            let low = r.read_bits(k)?;
+            Some(q.checked_shl(k as u32).unwrap_or(0) | low)
+        })
+    }
+}
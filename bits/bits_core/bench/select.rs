@@ -0,0 +1,56 @@
+//! Compares `broadword` and BMI2 `pdep` based select on random words.
+//!
+//! ```text
+//! bazel run -c opt //bits/bits_core:select_bench
+//! ```
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use bits_core::word::select64;
+
+const WORDS: usize = 1 << 12;
+const ROUNDS: usize = 1 << 8;
+
+// xorshift64*
+fn words(mut seed: u64) -> Vec<u64> {
+    (0..WORDS)
+        .map(|_| {
+            seed ^= seed >> 12;
+            seed ^= seed << 25;
+            seed ^= seed >> 27;
+            seed.wrapping_mul(0x2545_F491_4F6C_DD1D)
+        })
+        .collect()
+}
+
+fn run(name: &str, words: &[u64], select: fn(u64, usize) -> Option<usize>) -> Duration {
+    let now = Instant::now();
+    let mut sum = 0;
+    for _ in 0..ROUNDS {
+        for &w in words {
+            let n = (w.count_ones() / 2) as usize;
+            sum += black_box(select)(black_box(w), n).unwrap_or(0);
+        }
+    }
+    let elapsed = now.elapsed();
+    let per_op = elapsed.as_secs_f64() * 1e9 / (WORDS * ROUNDS) as f64;
+    println!("{name:>9}: {per_op:6.2} ns/op (checksum {sum})");
+    elapsed
+}
+
+fn main() {
+    let words = words(0x9E37_79B9_7F4A_7C15);
+    for &w in &words {
+        for n in 0..w.count_ones() as usize {
+            assert_eq!(select64::broadword(w, n), select64::bmi2(w, n));
+        }
+    }
+
+    if !select64::has_bmi2() {
+        println!("bmi2 is not available, select1 falls back to broadword");
+    }
+    let b = run("broadword", &words, select64::broadword);
+    let p = run("bmi2", &words, select64::bmi2);
+    println!("  speedup: {:.2}x", b.as_secs_f64() / p.as_secs_f64());
+}
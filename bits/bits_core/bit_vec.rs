@@ -1,5 +1,7 @@
-use std::borrow::{Borrow, BorrowMut};
-use std::ops;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::borrow::{Borrow, BorrowMut};
+use core::ops;
 
 use crate::{Bits, Block};
 
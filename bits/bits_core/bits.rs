@@ -1,9 +1,12 @@
-use std::borrow::ToOwned;
-use std::mem;
-use std::ops::{Range, RangeBounds};
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::mem;
+use core::ops::{Range, RangeBounds};
 
 use crate::block::{BlockMut, Count, Excess, Pack, Rank, Select};
-use crate::{BitVec, Block, Word};
+use crate::mask::helper::Assign;
+use crate::{BitVec, BitsError, Block, Word};
 
 #[derive(Hash, Debug)]
 #[repr(transparent)]
@@ -127,6 +130,101 @@ impl<T: BlockMut> Bits<T> {
         let (i, o) = bit::addr(i, T::BITS);
         self.data[i].set0(o)
     }
+
+    /// Enables the bit at `i`, or returns an error if `i` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bits_core::{Bits, BitsError};
+    /// let mut v = [0u8; 2];
+    /// let bits = Bits::new_mut(&mut v);
+    /// assert_eq!(bits.try_set1(15), Ok(()));
+    /// assert_eq!(bits.try_set1(16), Err(BitsError::OutOfBounds { index: 16, len: 16 }));
+    /// assert_eq!(bits.try_rank1(..), Ok(1));
+    /// assert_eq!(bits.try_rank1(..17), Err(BitsError::InvalidRange { start: 0, end: 17, len: 16 }));
+    /// assert_eq!(bits.try_select1(0), Ok(15));
+    /// assert_eq!(bits.try_select1(1), Err(BitsError::OutOfBounds { index: 1, len: 1 }));
+    /// ```
+    #[inline]
+    pub fn try_set1(&mut self, i: usize) -> Result<(), BitsError> {
+        BitsError::check_index(i, self.bits())?;
+        self.set1(i);
+        Ok(())
+    }
+
+    /// Disables the bit at `i`, or returns an error if `i` is out of bounds.
+    #[inline]
+    pub fn try_set0(&mut self, i: usize) -> Result<(), BitsError> {
+        BitsError::check_index(i, self.bits())?;
+        self.set0(i);
+        Ok(())
+    }
+}
+
+macro_rules! impl_try_assign {
+    ($( $try_op:ident $op:ident ),*) => {
+        impl<A> Bits<A> {
+            $(
+                #[doc = concat!("Same as `Assign::", stringify!($op), "`, but returns an error if the numbers of bits differ.")]
+                #[inline]
+                pub fn $try_op<B>(&mut self, that: &Bits<B>) -> Result<(), BitsError>
+                where
+                    A: Block + Assign<B>,
+                    B: Block,
+                {
+                    BitsError::check_len(self.bits(), that.bits())?;
+                    Assign::$op(self, that);
+                    Ok(())
+                }
+            )*
+        }
+    };
+}
+impl_try_assign!(try_and and, try_not not, try_or or, try_xor xor);
+
+impl<T: Word> Bits<T> {
+    /// Enables all bits in the given range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bits_core::Bits;
+    /// let mut v = [0u8; 4];
+    /// let bits = Bits::new_mut(&mut v);
+    /// bits.set1_range(5..20);
+    /// assert_eq!(bits.as_slice(), [0b1110_0000, 0b1111_1111, 0b0000_1111, 0]);
+    /// bits.set0_range(7..=12);
+    /// assert_eq!(bits.as_slice(), [0b0110_0000, 0b1110_0000, 0b0000_1111, 0]);
+    /// ```
+    pub fn set1_range<R: RangeBounds<usize>>(&mut self, r: R) {
+        let Range { start, end } = bit::bounded(&r, 0, self.bits());
+        for (i, r, full) in bit::aligned(start, end, T::BITS) {
+            if full {
+                self.data[i] = !T::empty();
+            } else {
+                self.data[i] |= mask::<T>(r);
+            }
+        }
+    }
+
+    /// Disables all bits in the given range.
+    pub fn set0_range<R: RangeBounds<usize>>(&mut self, r: R) {
+        let Range { start, end } = bit::bounded(&r, 0, self.bits());
+        for (i, r, full) in bit::aligned(start, end, T::BITS) {
+            if full {
+                self.data[i] = T::empty();
+            } else {
+                self.data[i] &= !mask::<T>(r);
+            }
+        }
+    }
+}
+
+/// Returns a word in which bits in `r` are enabled. `r` must be non-empty.
+#[inline]
+fn mask<T: Word>(r: Range<usize>) -> T {
+    (!T::empty() >> (T::BITS - r.len())) << r.start
 }
 
 impl<T: Block + Count> Bits<T> {
@@ -209,28 +307,9 @@ impl<T: Block + Rank> Bits<T> {
     /// Counts occurrences of `1` in the given range.
     pub fn rank1<R: RangeBounds<usize>>(&self, r: R) -> usize {
         let Range { start, end } = bit::bounded(&r, 0, self.bits());
-
-        // TODO: benchmark
-        // bit::chunks(start, end, B::BITS)
-        //     .map(|(index, len)| {
-        //         let (i, p) = bit::addr(index, B::BITS);
-        //         self.get(i)
-        //             .map_or(0, |b| if len == B::BITS { b.count1() } else { b.rank1(p..p + len) })
-        //     })
-        //     .sum()
-
-        if self.data.is_empty() {
-            return 0;
-        }
-        let (i, p) = bit::addr(start, T::BITS);
-        let (j, q) = bit::addr(end, T::BITS);
-        if i == j {
-            self.data[i].rank1(p..q)
-        } else {
-            self.data[i].rank1(p..)
-                + Bits::new(&self.data[i + 1..j]).count1()
-                + self.data.get(j).map_or(0, |b| b.rank1(..q))
-        }
+        bit::aligned(start, end, T::BITS)
+            .map(|(i, r, full)| if full { self.data[i].count1() } else { self.data[i].rank1(r) })
+            .sum()
     }
 
     /// Counts occurrences of `0` in the given range.
@@ -239,6 +318,12 @@ impl<T: Block + Rank> Bits<T> {
         let r = bit::bounded(&r, 0, self.bits());
         r.len() - self.rank1(r)
     }
+
+    /// Counts occurrences of `1` in `r`, or returns an error if `r` is out of bounds.
+    #[inline]
+    pub fn try_rank1<R: RangeBounds<usize>>(&self, r: R) -> Result<usize, BitsError> {
+        BitsError::check_range(&r, self.bits()).map(|r| self.rank1(r))
+    }
 }
 
 impl<T: Block + Excess> Bits<T> {
@@ -289,45 +374,77 @@ impl<T: Block + Select> Bits<T> {
         }
         None
     }
-}
 
-fn range_over<T: Block>(s: usize, e: usize, mut f: impl FnMut(usize, usize, usize) -> bool) {
-    assert!(s <= e);
-    if s == e {
-        return;
+    /// Returns the position of the n-th 1, or an error if `n` is not less than `self.count1()`.
+    #[inline]
+    pub fn try_select1(&self, n: usize) -> Result<usize, BitsError> {
+        self.select1(n).ok_or_else(|| BitsError::OutOfBounds { index: n, len: self.count1() })
+    }
+
+    /// Returns the position of the n-th 1 in `r`, counting from the start of `r`.
+    /// Blocks before `r` are not visited.
+    ///
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_core::BitVec;
+    /// let mut bv = BitVec::<u8>::new(64);
+    /// for i in [3, 9, 10, 40, 63] {
+    ///     bv.set1(i);
+    /// }
+    /// assert_eq!(bv.select1_in(4..41, 0), Some(9));
+    /// assert_eq!(bv.select1_in(4..41, 2), Some(40));
+    /// assert_eq!(bv.select1_in(4..40, 2), None);
+    /// assert_eq!(bv.select1_after(10, 1), Some(40));
+    /// assert_eq!(bv.select1_after(64, 0), None);
+    /// assert_eq!(bv.select0_in(8.., 2), Some(12));
+    /// ```
+    #[inline]
+    pub fn select1_in<R: RangeBounds<usize>>(&self, r: R, n: usize) -> Option<usize> {
+        let Range { start, end } = bit::bounded(&r, 0, self.bits());
+        self.select_in::<false>(start, end, n)
     }
 
-    let (q0, r0) = bit::addr(s, T::BITS);
-    let (q1, r1) = bit::addr(e, T::BITS);
+    /// Returns the position of the n-th 0 in `r`, counting from the start of `r`.
+    #[inline]
+    pub fn select0_in<R: RangeBounds<usize>>(&self, r: R, n: usize) -> Option<usize> {
+        let Range { start, end } = bit::bounded(&r, 0, self.bits());
+        self.select_in::<true>(start, end, n)
+    }
 
-    if q0 == q1 {
-        f(q0, r0, r1);
-        return;
+    /// Returns the position of the n-th 1 at or after `i`.
+    #[inline]
+    pub fn select1_after(&self, i: usize, n: usize) -> Option<usize> {
+        self.select1_in(i.., n)
     }
 
-    use std::iter::{once, repeat};
-    for (index, cur) in (q0..q1).zip(once(r0).chain(repeat(0))) {
-        if !f(index, cur, T::BITS) {
-            return;
+    /// Finds the `n`-th 1 (or 0 if `ZERO`) in `[s, e)` by searching blocks overlapping the range.
+    fn select_in<const ZERO: bool>(&self, s: usize, e: usize, mut n: usize) -> Option<usize> {
+        let count = |b: &T, r: Range<usize>| if ZERO { r.len() - b.rank1(r) } else { b.rank1(r) };
+        for (i, r, _) in bit::aligned(s, e, T::BITS) {
+            let b = &self.data[i];
+            let c = count(b, r.clone());
+            if n < c {
+                let n = n + count(b, 0..r.start);
+                let found = if ZERO { b.select0(n) } else { b.select1(n) };
+                return Some(i * T::BITS + found.expect("select must be ok"));
+            }
+            n -= c;
         }
+        None
     }
-
-    f(q1, 0, r1);
 }
 
 impl<B: Block + Pack> Bits<B> {
     /// Writes `n` bits of the given to `[i, i+n)`.
     pub fn pack<T: Word>(&mut self, i: usize, n: usize, bits: T) {
         let mut cur = 0;
-        range_over::<B>(i, i + n, |idx, s, e| {
-            (idx < self.data.len())
-                .then(|| {
-                    let len = e - s;
-                    self.data[idx].pack::<T>(s, len, bits.unpack(cur, len));
-                    cur += len;
-                })
-                .is_some()
-        });
+        let blocks = self.data.len();
+        for (idx, r, _) in bit::aligned(i, i + n, B::BITS).take_while(|(idx, ..)| *idx < blocks) {
+            let len = r.len();
+            self.data[idx].pack::<T>(r.start, len, bits.unpack(cur, len));
+            cur += len;
+        }
     }
 
     /// Reads `n` bits from `i`, and returns it as the lowest `n` bits of `T`.
@@ -346,15 +463,14 @@ impl<B: Block + Pack> Bits<B> {
     pub fn unpack<T: Word>(&self, i: usize, n: usize) -> T {
         let mut cur = 0;
         let mut out = T::empty();
-        range_over::<B>(i, i + n, |idx, s, e| {
-            (idx < self.data.len() && cur < T::BITS)
-                .then(|| {
-                    let len = e - s;
-                    out |= self.data[idx].unpack::<T>(s, len) << cur;
-                    cur += len;
-                })
-                .is_some()
-        });
+        for (idx, r, _) in bit::aligned(i, i + n, B::BITS).take_while(|(idx, ..)| *idx < self.data.len()) {
+            if cur >= T::BITS {
+                break;
+            }
+            let len = r.len();
+            out |= self.data[idx].unpack::<T>(r.start, len) << cur;
+            cur += len;
+        }
         out
     }
 }
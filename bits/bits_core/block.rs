@@ -12,6 +12,8 @@ pub use pack::Pack;
 pub use rank::Rank;
 pub use select::Select;
 
+use alloc::boxed::Box;
+
 use crate::Bits;
 
 pub trait Block: Clone {
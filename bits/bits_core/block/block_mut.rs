@@ -1,5 +1,8 @@
+use alloc::boxed::Box;
+
 use super::Block;
 use crate::bits::Bits;
+use crate::BitsError;
 
 pub trait BlockMut: Block {
     /// Enables the bit at the given index `i`.
@@ -7,6 +10,22 @@ pub trait BlockMut: Block {
 
     /// Disables the bit at the given index `i`.
     fn set0(&mut self, i: usize);
+
+    /// Enables the bit at `i`, or returns an error if `i` is out of bounds.
+    #[inline]
+    fn try_set1(&mut self, i: usize) -> Result<(), BitsError> {
+        BitsError::check_index(i, Self::BITS)?;
+        self.set1(i);
+        Ok(())
+    }
+
+    /// Disables the bit at `i`, or returns an error if `i` is out of bounds.
+    #[inline]
+    fn try_set0(&mut self, i: usize) -> Result<(), BitsError> {
+        BitsError::check_index(i, Self::BITS)?;
+        self.set0(i);
+        Ok(())
+    }
 }
 
 impl<B: Copy + BlockMut, const N: usize> BlockMut for [B; N] {
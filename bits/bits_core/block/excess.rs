@@ -1,4 +1,4 @@
-use std::ops::RangeBounds;
+use core::ops::RangeBounds;
 
 use crate::block::Rank;
 
@@ -19,7 +19,7 @@ impl<T: Rank> Excess for T {
 }
 
 pub(crate) mod helper {
-    use std::ops::RangeBounds;
+    use core::ops::RangeBounds;
 
     use crate::block::Rank;
 
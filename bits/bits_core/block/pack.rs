@@ -1,3 +1,5 @@
+use alloc::boxed::Box;
+
 use crate::bits::Bits;
 use crate::block::{Block, BlockMut};
 use crate::word::Word;
@@ -1,7 +1,9 @@
-use std::ops::RangeBounds;
+use alloc::boxed::Box;
+use core::ops::RangeBounds;
 
 use crate::bits::Bits;
 use crate::block::{Block, Count};
+use crate::BitsError;
 
 pub trait Rank: Count {
     #[inline]
@@ -15,6 +17,12 @@ pub trait Rank: Count {
         let r = bit::bounded(&r, 0, Self::BITS);
         r.len() - self.rank1(r)
     }
+
+    /// Counts occurrences of `1` in `r`, or returns an error if `r` is out of bounds.
+    #[inline]
+    fn try_rank1<R: RangeBounds<usize>>(&self, r: R) -> Result<usize, BitsError> {
+        BitsError::check_range(&r, Self::BITS).map(|r| self.rank1(r))
+    }
 }
 
 impl<B: Copy + Block + Rank, const N: usize> Rank for [B; N] {
@@ -24,7 +32,7 @@ impl<B: Copy + Block + Rank, const N: usize> Rank for [B; N] {
     }
     #[inline]
     fn rank0<R: RangeBounds<usize>>(&self, r: R) -> usize {
-        Bits::new(self.as_slice()).rank1(r)
+        Bits::new(self.as_slice()).rank0(r)
     }
 }
 
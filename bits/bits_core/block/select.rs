@@ -1,5 +1,9 @@
+use alloc::boxed::Box;
+use core::ops::RangeBounds;
+
 use crate::bits::Bits;
 use crate::block::{Block, Rank};
+use crate::BitsError;
 
 pub trait Select: Rank {
     #[inline]
@@ -7,20 +11,53 @@ pub trait Select: Rank {
         helper::search1(self, n)
     }
 
-    // #[inline]
-    // fn select1_from(&self, i: usize, n: usize) -> Option<usize> {
-    //     self.select1(self.rank1(..i) + n).map(|pos| pos - i)
-    // }
+    /// Returns the position of the n-th 1 in `r`, counting from the start of `r`.
+    /// The position is relative to the block, not to `r`.
+    #[inline]
+    fn select1_in<R: RangeBounds<usize>>(&self, r: R, n: usize) -> Option<usize> {
+        let r = bit::bounded(&r, 0, Self::BITS);
+        let pos = self.select1(self.rank1(..r.start).checked_add(n)?)?;
+        (pos < r.end).then_some(pos)
+    }
+
+    /// Returns the position of the n-th 1 at or after `i`.
+    ///
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_core::block::*;
+    /// let b: u64 = 0b_1011_0110;
+    /// assert_eq!(b.select1_after(3, 0), Some(4));
+    /// assert_eq!(b.select1_after(4, 2), Some(7));
+    /// assert_eq!(b.select1_after(4, 3), None);
+    /// assert_eq!(b.select1_in(2..5, 1), Some(4));
+    /// assert_eq!(b.select1_in(2..5, 2), None);
+    /// assert_eq!(b.select0_in(3.., 1), Some(6));
+    /// assert_eq!(b.select0_in(..3, 1), None);
+    /// ```
+    #[inline]
+    fn select1_after(&self, i: usize, n: usize) -> Option<usize> {
+        self.select1_in(i.., n)
+    }
 
     #[inline]
     fn select0(&self, n: usize) -> Option<usize> {
         helper::search0(self, n)
     }
 
-    // #[inline]
-    // fn select0_from(&self, i: usize, n: usize) -> Option<usize> {
-    //     self.select0(self.rank0(..i) + n).map(|pos| pos - i)
-    // }
+    /// Returns the position of the n-th 0 in `r`, counting from the start of `r`.
+    #[inline]
+    fn select0_in<R: RangeBounds<usize>>(&self, r: R, n: usize) -> Option<usize> {
+        let r = bit::bounded(&r, 0, Self::BITS);
+        let pos = self.select0(self.rank0(..r.start).checked_add(n)?)?;
+        (pos < r.end).then_some(pos)
+    }
+
+    /// Returns the position of the n-th 1, or an error if `n` is not less than `self.count1()`.
+    #[inline]
+    fn try_select1(&self, n: usize) -> Result<usize, BitsError> {
+        self.select1(n).ok_or_else(|| BitsError::OutOfBounds { index: n, len: self.count1() })
+    }
 }
 
 mod helper {
@@ -68,6 +105,14 @@ impl<B: Copy + Block + Select, const N: usize> Select for [B; N] {
     fn select0(&self, n: usize) -> Option<usize> {
         Bits::new(self.as_slice()).select0(n)
     }
+    #[inline]
+    fn select1_in<R: RangeBounds<usize>>(&self, r: R, n: usize) -> Option<usize> {
+        Bits::new(self.as_slice()).select1_in(r, n)
+    }
+    #[inline]
+    fn select0_in<R: RangeBounds<usize>>(&self, r: R, n: usize) -> Option<usize> {
+        Bits::new(self.as_slice()).select0_in(r, n)
+    }
 }
 
 impl<B: Block + Select> Select for Box<B> {
@@ -79,4 +124,12 @@ impl<B: Block + Select> Select for Box<B> {
     fn select0(&self, n: usize) -> Option<usize> {
         self.as_ref().select0(n)
     }
+    #[inline]
+    fn select1_in<R: RangeBounds<usize>>(&self, r: R, n: usize) -> Option<usize> {
+        self.as_ref().select1_in(r, n)
+    }
+    #[inline]
+    fn select0_in<R: RangeBounds<usize>>(&self, r: R, n: usize) -> Option<usize> {
+        self.as_ref().select0_in(r, n)
+    }
 }
@@ -0,0 +1,90 @@
+use core::fmt;
+use core::ops::{Bound, Range, RangeBounds};
+
+/// An error returned by the `try_*` variants of bits operations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BitsError {
+    /// `index` is not less than `len`.
+    OutOfBounds { index: usize, len: usize },
+    /// The range `start..end` is not within `0..len`.
+    InvalidRange { start: usize, end: usize, len: usize },
+    /// A number does not fit in the integer type which holds it.
+    Overflow,
+    /// The operands of an assign operation have different numbers of bits, `lhs` and `rhs`.
+    LengthMismatch { lhs: usize, rhs: usize },
+    /// Bytes to decode are truncated or inconsistent.
+    InvalidBytes,
+}
+
+impl fmt::Display for BitsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BitsError::OutOfBounds { index, len } => {
+                write!(f, "index out of bounds: the len is {len} but the index is {index}")
+            }
+            BitsError::InvalidRange { start, end, len } => {
+                write!(f, "range {start}..{end} out of bounds: the len is {len}")
+            }
+            BitsError::Overflow => f.write_str("integer overflow"),
+            BitsError::LengthMismatch { lhs, rhs } => {
+                write!(f, "length mismatch: {lhs} and {rhs} bits")
+            }
+            BitsError::InvalidBytes => f.write_str("invalid bytes"),
+        }
+    }
+}
+
+impl core::error::Error for BitsError {}
+
+impl BitsError {
+    /// Returns `Ok` if `index < len`.
+    #[inline]
+    pub fn check_index(index: usize, len: usize) -> Result<(), BitsError> {
+        if index < len {
+            Ok(())
+        } else {
+            Err(BitsError::OutOfBounds { index, len })
+        }
+    }
+
+    /// Converts `r` into a bounded range, and returns `Ok` if it is within `0..len`.
+    ///
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_core::BitsError;
+    /// assert_eq!(BitsError::check_range(&(..), 10), Ok(0..10));
+    /// assert_eq!(BitsError::check_range(&(3..=9), 10), Ok(3..10));
+    /// assert_eq!(BitsError::check_range(&(3..=10), 10), Err(BitsError::InvalidRange { start: 3, end: 11, len: 10 }));
+    /// assert_eq!(BitsError::check_range(&(5..3), 10), Err(BitsError::InvalidRange { start: 5, end: 3, len: 10 }));
+    /// assert_eq!(BitsError::check_range(&(..=usize::MAX), 10), Err(BitsError::Overflow));
+    /// ```
+    pub fn check_range<R: RangeBounds<usize>>(r: &R, len: usize) -> Result<Range<usize>, BitsError> {
+        let start = match r.start_bound() {
+            Bound::Included(&s) => s,
+            Bound::Excluded(&s) => s.checked_add(1).ok_or(BitsError::Overflow)?,
+            Bound::Unbounded => 0,
+        };
+        let end = match r.end_bound() {
+            Bound::Included(&e) => e.checked_add(1).ok_or(BitsError::Overflow)?,
+            Bound::Excluded(&e) => e,
+            Bound::Unbounded => len,
+        };
+        if start <= end && end <= len {
+            Ok(start..end)
+        } else {
+            Err(BitsError::InvalidRange { start, end, len })
+        }
+    }
+
+    /// Returns `Ok` if `lhs == rhs`.
+    #[inline]
+    pub fn check_len(lhs: usize, rhs: usize) -> Result<(), BitsError> {
+        if lhs == rhs {
+            Ok(())
+        } else {
+            Err(BitsError::LengthMismatch { lhs, rhs })
+        }
+    }
+}
@@ -1,15 +1,27 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 mod bit_vec;
 mod bits;
 pub mod block;
+mod error;
 pub mod mask;
+pub mod rank_select;
+mod space;
 pub mod word;
 
 pub use bit_vec::BitVec;
 pub use bits::Bits;
 use block::Block;
+pub use error::BitsError;
+pub use rank_select::{BitLen, Excess, RankSelect};
+pub use space::SpaceUsage;
 use word::Word;
 
+use alloc::vec::Vec;
+
 pub(crate) fn make<T: Block>(n: usize) -> Vec<T> {
-    use std::iter::from_fn;
+    use core::iter::from_fn;
     from_fn(|| Some(T::empty())).take(bit::blocks(n, T::BITS)).collect()
 }
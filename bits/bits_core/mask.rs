@@ -5,10 +5,10 @@ mod xor;
 
 pub mod helper;
 
-use std::borrow::Cow;
-use std::cmp::Ordering;
-use std::iter::Enumerate;
-use std::slice;
+use alloc::borrow::Cow;
+use core::cmp::Ordering;
+use core::iter::Enumerate;
+use core::slice;
 
 pub use and::*;
 pub use not::*;
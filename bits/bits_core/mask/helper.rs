@@ -1,6 +1,6 @@
-use std::borrow::{Cow, ToOwned};
-use std::boxed::Box;
-use std::vec::Vec;
+use alloc::borrow::{Cow, ToOwned};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
 
 use crate::Bits;
 
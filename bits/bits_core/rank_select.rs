@@ -0,0 +1,209 @@
+//! Traits for queries over a whole bit vector.
+//!
+//! [`BitLen`], [`RankSelect`] and [`Excess`] are implemented by [`Bits`] and every block
+//! which implements [`Select`], and also by dictionaries in other crates such as `Pop`,
+//! so that algorithms can be generic over the backing structure.
+//! Pass `&*bit_vec` for a [`BitVec`](crate::BitVec).
+//!
+//! Methods are named after what they count, e.g. `rank_ones` for `rank1` of the block traits,
+//! so that both can be in scope for a block without making calls ambiguous.
+
+use core::ops::RangeBounds;
+
+use crate::block::Select;
+use crate::Bits;
+
+/// The number of bits.
+pub trait BitLen {
+    /// Returns the number of bits.
+    fn bits(&self) -> usize;
+
+    /// Returns a bit at `i`, or `None` if out of bounds.
+    fn bit(&self, i: usize) -> Option<bool>;
+}
+
+/// Rank and select over a whole bit vector.
+///
+/// # Examples
+///
+/// ```
+/// # use bits_core::block::*;
+/// # use bits_core::{BitVec, RankSelect};
+/// fn median<T: ?Sized + RankSelect>(bits: &T) -> Option<usize> {
+///     bits.select_one(bits.ones() / 2)
+/// }
+///
+/// let mut bv = BitVec::<u64>::new(100);
+/// bv.set1(3);
+/// bv.set1(50);
+/// bv.set1(70);
+/// assert_eq!(median(&*bv), Some(50));
+///
+/// let block = bv.as_slice()[1];
+/// assert_eq!(median(&block), Some(70 - 64));
+/// assert_eq!(block.rank1(..), block.ones());
+/// ```
+pub trait RankSelect: BitLen {
+    /// Counts occurrences of `1`.
+    #[inline]
+    fn ones(&self) -> usize {
+        self.rank_ones(..)
+    }
+
+    /// Counts occurrences of `0`.
+    #[inline]
+    fn zeros(&self) -> usize {
+        self.bits() - self.ones()
+    }
+
+    /// Counts occurrences of `1` in `r`.
+    fn rank_ones<R: RangeBounds<usize>>(&self, r: R) -> usize;
+
+    /// Counts occurrences of `0` in `r`.
+    #[inline]
+    fn rank_zeros<R: RangeBounds<usize>>(&self, r: R) -> usize {
+        let r = bit::bounded(&r, 0, self.bits());
+        r.len() - self.rank_ones(r)
+    }
+
+    /// Returns the position of the n-th 1, indexed starting from zero.
+    fn select_one(&self, n: usize) -> Option<usize>;
+
+    /// Returns the position of the n-th 0, indexed starting from zero.
+    fn select_zero(&self, n: usize) -> Option<usize>;
+
+    /// Returns the position of the n-th 1 in `r`, counting from the start of `r`.
+    #[inline]
+    fn select_one_in<R: RangeBounds<usize>>(&self, r: R, n: usize) -> Option<usize> {
+        let r = bit::bounded(&r, 0, self.bits());
+        let pos = self.select_one(self.rank_ones(..r.start).checked_add(n)?)?;
+        (pos < r.end).then_some(pos)
+    }
+
+    /// Returns the position of the n-th 0 in `r`, counting from the start of `r`.
+    #[inline]
+    fn select_zero_in<R: RangeBounds<usize>>(&self, r: R, n: usize) -> Option<usize> {
+        let r = bit::bounded(&r, 0, self.bits());
+        let pos = self.select_zero(self.rank_zeros(..r.start).checked_add(n)?)?;
+        (pos < r.end).then_some(pos)
+    }
+
+    /// Returns the position of the n-th 1 at or after `i`.
+    #[inline]
+    fn select_one_after(&self, i: usize, n: usize) -> Option<usize> {
+        self.select_one_in(i.., n)
+    }
+}
+
+/// Excess of ones over zeros, or zeros over ones, in a range.
+pub trait Excess: RankSelect {
+    fn excess_ones<R: RangeBounds<usize>>(&self, r: R) -> Option<usize>;
+    fn excess_zeros<R: RangeBounds<usize>>(&self, r: R) -> Option<usize>;
+}
+
+impl<T: ?Sized + RankSelect> Excess for T {
+    #[inline]
+    fn excess_ones<R: RangeBounds<usize>>(&self, r: R) -> Option<usize> {
+        let r = bit::bounded(&r, 0, self.bits());
+        let (len, ones) = (r.len(), self.rank_ones(r));
+        ones.checked_sub(len - ones)
+    }
+    #[inline]
+    fn excess_zeros<R: RangeBounds<usize>>(&self, r: R) -> Option<usize> {
+        let r = bit::bounded(&r, 0, self.bits());
+        let (len, ones) = (r.len(), self.rank_ones(r));
+        (len - ones).checked_sub(ones)
+    }
+}
+
+impl<B: Select> BitLen for B {
+    #[inline]
+    fn bits(&self) -> usize {
+        B::BITS
+    }
+    #[inline]
+    fn bit(&self, i: usize) -> Option<bool> {
+        self.test(i)
+    }
+}
+
+impl<B: Select> RankSelect for B {
+    #[inline]
+    fn ones(&self) -> usize {
+        self.count1()
+    }
+    #[inline]
+    fn zeros(&self) -> usize {
+        self.count0()
+    }
+    #[inline]
+    fn rank_ones<R: RangeBounds<usize>>(&self, r: R) -> usize {
+        self.rank1(r)
+    }
+    #[inline]
+    fn rank_zeros<R: RangeBounds<usize>>(&self, r: R) -> usize {
+        self.rank0(r)
+    }
+    #[inline]
+    fn select_one(&self, n: usize) -> Option<usize> {
+        self.select1(n)
+    }
+    #[inline]
+    fn select_zero(&self, n: usize) -> Option<usize> {
+        self.select0(n)
+    }
+    #[inline]
+    fn select_one_in<R: RangeBounds<usize>>(&self, r: R, n: usize) -> Option<usize> {
+        Select::select1_in(self, r, n)
+    }
+    #[inline]
+    fn select_zero_in<R: RangeBounds<usize>>(&self, r: R, n: usize) -> Option<usize> {
+        Select::select0_in(self, r, n)
+    }
+}
+
+impl<T: Select> BitLen for Bits<T> {
+    #[inline]
+    fn bits(&self) -> usize {
+        self.bits()
+    }
+    #[inline]
+    fn bit(&self, i: usize) -> Option<bool> {
+        self.test(i)
+    }
+}
+
+impl<T: Select> RankSelect for Bits<T> {
+    #[inline]
+    fn ones(&self) -> usize {
+        self.count1()
+    }
+    #[inline]
+    fn zeros(&self) -> usize {
+        self.count0()
+    }
+    #[inline]
+    fn rank_ones<R: RangeBounds<usize>>(&self, r: R) -> usize {
+        self.rank1(r)
+    }
+    #[inline]
+    fn rank_zeros<R: RangeBounds<usize>>(&self, r: R) -> usize {
+        self.rank0(r)
+    }
+    #[inline]
+    fn select_one(&self, n: usize) -> Option<usize> {
+        self.select1(n)
+    }
+    #[inline]
+    fn select_zero(&self, n: usize) -> Option<usize> {
+        self.select0(n)
+    }
+    #[inline]
+    fn select_one_in<R: RangeBounds<usize>>(&self, r: R, n: usize) -> Option<usize> {
+        self.select1_in(r, n)
+    }
+    #[inline]
+    fn select_zero_in<R: RangeBounds<usize>>(&self, r: R, n: usize) -> Option<usize> {
+        self.select0_in(r, n)
+    }
+}
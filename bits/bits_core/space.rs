@@ -0,0 +1,96 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::mem;
+
+use crate::word::Lanes;
+use crate::{BitVec, Bits};
+
+/// Reports the memory used by a bits container.
+///
+/// # Examples
+///
+/// ```
+/// # use bits_core::{BitVec, SpaceUsage};
+/// let v = BitVec::<u64>::new(1000);
+/// assert_eq!(v.heap_bytes(), 16 * 8);
+/// assert_eq!(v.total_bytes(), 16 * 8 + std::mem::size_of::<Vec<u64>>());
+/// assert!(v.bits_per_element(v.bits()) > 1.0);
+/// ```
+pub trait SpaceUsage {
+    /// Returns the number of bytes allocated on the heap, excluding `size_of_val(self)`.
+    fn heap_bytes(&self) -> usize;
+
+    /// Returns the number of bytes including the inline size of `self`.
+    #[inline]
+    fn total_bytes(&self) -> usize {
+        mem::size_of_val(self) + self.heap_bytes()
+    }
+
+    /// Returns the number of bits spent per element when `self` holds `n` elements,
+    /// e.g. the number of bits for a dense bitmap, or the number of ones for a sparse set.
+    #[inline]
+    fn bits_per_element(&self, n: usize) -> f64 {
+        (self.total_bytes() * 8) as f64 / n as f64
+    }
+}
+
+macro_rules! impls_for_primitive {
+    ($( $Ty:ty )*) => ($(
+        impl SpaceUsage for $Ty {
+            #[inline]
+            fn heap_bytes(&self) -> usize {
+                0
+            }
+        }
+    )*)
+}
+impls_for_primitive!(u8 u16 u32 u64 u128 usize);
+
+impl<const L: usize> SpaceUsage for Lanes<L> {
+    #[inline]
+    fn heap_bytes(&self) -> usize {
+        0
+    }
+}
+
+impl<T: SpaceUsage> SpaceUsage for [T] {
+    #[inline]
+    fn heap_bytes(&self) -> usize {
+        self.iter().map(T::heap_bytes).sum()
+    }
+}
+
+impl<T: SpaceUsage, const N: usize> SpaceUsage for [T; N] {
+    #[inline]
+    fn heap_bytes(&self) -> usize {
+        self.as_slice().heap_bytes()
+    }
+}
+
+impl<T: SpaceUsage> SpaceUsage for Vec<T> {
+    #[inline]
+    fn heap_bytes(&self) -> usize {
+        self.capacity() * mem::size_of::<T>() + self.as_slice().heap_bytes()
+    }
+}
+
+impl<T: SpaceUsage + ?Sized> SpaceUsage for Box<T> {
+    #[inline]
+    fn heap_bytes(&self) -> usize {
+        self.as_ref().total_bytes()
+    }
+}
+
+impl<T: SpaceUsage> SpaceUsage for Bits<T> {
+    #[inline]
+    fn heap_bytes(&self) -> usize {
+        self.data.heap_bytes()
+    }
+}
+
+impl<T: SpaceUsage> SpaceUsage for BitVec<T> {
+    #[inline]
+    fn heap_bytes(&self) -> usize {
+        self.data.heap_bytes()
+    }
+}
@@ -1,8 +1,11 @@
-use std::fmt;
-use std::ops::{self, Range, RangeBounds};
+use core::fmt;
+use core::ops::{self, Range, RangeBounds};
 
 use crate::block::*;
-use crate::{Bits, Block};
+use crate::Block;
+
+mod lanes;
+pub use lanes::{Lanes, ParseLanesError, U256, U512};
 
 /// Integer with a fixed-sized bits.
 pub trait Word:
@@ -56,16 +59,62 @@ impl WordSelectHelper for u64 {
     #[inline]
     fn select1(self, n: usize) -> Option<usize> {
         (n < self.count1()).then(|| {
-            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-            if is_x86_feature_detected!("bmi2") {
-                use std::arch::x86_64::{_pdep_u64, _tzcnt_u64};
-                return unsafe { _tzcnt_u64(_pdep_u64(1 << n, self)) as usize };
+            #[cfg(target_arch = "x86_64")]
+            if select64::has_bmi2() {
+                return unsafe { pdep(self, n) };
             }
             broadword(self, n as u64)
         })
     }
 }
 
+/// Selects the `n`-th set bit by depositing `1 << n` to the set bits of `x`.
+///
+/// Note that `pdep` is microcoded and much slower than `broadword` on AMD processors before Zen 3.
+///
+/// # Safety
+///
+/// The caller must ensure that the CPU supports BMI2, and that `n < x.count_ones()`.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "bmi2")]
+#[inline]
+unsafe fn pdep(x: u64, n: usize) -> usize {
+    core::arch::x86_64::_pdep_u64(1 << n, x).trailing_zeros() as usize
+}
+
+/// Select implementations for `u64`, exposed only for benchmarks.
+#[doc(hidden)]
+pub mod select64 {
+    use crate::block::Count;
+
+    /// Returns true if `pdep` is available at runtime.
+    /// Without `std`, only target features enabled at compile time are known.
+    #[inline]
+    pub fn has_bmi2() -> bool {
+        #[cfg(all(target_arch = "x86_64", feature = "std"))]
+        {
+            std::is_x86_feature_detected!("bmi2")
+        }
+        #[cfg(not(all(target_arch = "x86_64", feature = "std")))]
+        {
+            cfg!(all(target_arch = "x86_64", target_feature = "bmi2"))
+        }
+    }
+
+    pub fn broadword(x: u64, n: usize) -> Option<usize> {
+        (n < x.count1()).then(|| super::broadword(x, n as u64))
+    }
+
+    /// Falls back to `broadword` if `has_bmi2()` is false.
+    pub fn bmi2(x: u64, n: usize) -> Option<usize> {
+        #[cfg(target_arch = "x86_64")]
+        if has_bmi2() {
+            return (n < x.count1()).then(|| unsafe { super::pdep(x, n) });
+        }
+        broadword(x, n)
+    }
+}
+
 // Sebastiano Vigna, “Broadword Implementation of Rank/Select Queries”
 // Returns 72 when not found.
 #[allow(clippy::many_single_char_names)]
@@ -132,8 +181,13 @@ impl WordSelectHelper for u128 {
     /// ```
     #[inline]
     fn select1(self, c: usize) -> Option<usize> {
-        let this = [self as u64, (self >> 64) as u64];
-        Bits::new(&this).select1(c)
+        let (lo, hi) = (self as u64, (self >> 64) as u64);
+        let k = lo.count1();
+        if c < k {
+            <u64 as WordSelectHelper>::select1(lo, c)
+        } else {
+            <u64 as WordSelectHelper>::select1(hi, c - k).map(|i| i + 64)
+        }
     }
 }
 
@@ -141,19 +195,19 @@ impl WordSelectHelper for usize {
     #[cfg(target_pointer_width = "32")]
     #[inline]
     fn select1(self, c: usize) -> Option<usize> {
-        (self as u32).select_word(c)
+        <u32 as WordSelectHelper>::select1(self as u32, c)
     }
 
     #[cfg(target_pointer_width = "64")]
     #[inline]
     fn select1(self, c: usize) -> Option<usize> {
-        (self as u64).select1(c)
+        <u64 as WordSelectHelper>::select1(self as u64, c)
     }
 
     #[cfg(target_pointer_width = "128")]
     #[inline]
     fn select1(self, c: usize) -> Option<usize> {
-        (self as u128).select_word(c)
+        <u128 as WordSelectHelper>::select1(self as u128, c)
     }
 }
 
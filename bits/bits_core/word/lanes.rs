@@ -0,0 +1,769 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt::{self, Write as _};
+use core::ops::{self, RangeBounds};
+
+use num::traits::{Bounded, CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, Num, NumCast, One, PrimInt, Saturating};
+use num::traits::{ToPrimitive, Zero};
+
+use crate::block::*;
+use crate::mask::helper;
+use crate::word::Word;
+use crate::Bits;
+
+/// An unsigned integer of `64 * L` bits, stored as `L` lanes of `u64` from the least significant one.
+///
+/// Arithmetic operations wrap around on overflow, and shifts by `BITS` or more yield zero.
+/// `count1`, `rank1` and `select1` work on each lane as a `u64`, so `Bits<Lanes<L>>` and `Pop<Lanes<L>>`
+/// can be used in the same way as `Bits<u64>`.
+///
+/// # Examples
+///
+/// ```
+/// # use bits_core::block::*;
+/// # use bits_core::word::{Word, U256};
+/// let mut w = U256::empty();
+/// w.set1(3);
+/// w.set1(200);
+/// assert_eq!(w.count1(), 2);
+/// assert_eq!(w.rank1(..200), 1);
+/// assert_eq!(w.select1(1), Some(200));
+/// assert_eq!(w.msb(), U256::from([0, 0, 0, 1 << 8]));
+/// assert_eq!(w.lsb(), U256::from([1 << 3, 0, 0, 0]));
+/// ```
+///
+/// `L` must not be zero, which is checked at compile time:
+///
+/// ```compile_fail
+/// # use bits_core::word::Lanes;
+/// let _ = Lanes::<0>::from([]);
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct Lanes<const L: usize>(pub [u64; L]);
+
+/// A 256-bit word.
+pub type U256 = Lanes<4>;
+
+/// A 512-bit word.
+pub type U512 = Lanes<8>;
+
+/// An error which can be returned when parsing [`Lanes`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseLanesError(());
+
+impl fmt::Display for ParseLanesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid digit or number too large")
+    }
+}
+
+impl core::error::Error for ParseLanesError {}
+
+impl<const L: usize> Lanes<L> {
+    pub const ZERO: Self = Lanes([0; L]);
+
+    pub const MAX: Self = Lanes([!0; L]);
+
+    pub const ONE: Self = {
+        assert!(L > 0, "Lanes must have at least one lane");
+        let mut lanes = [0; L];
+        lanes[0] = 1;
+        Lanes(lanes)
+    };
+
+    const BITS: usize = {
+        assert!(L > 0, "Lanes must have at least one lane");
+        64 * L
+    };
+
+    fn from_u128(x: u128) -> Option<Self> {
+        const { assert!(L > 0, "Lanes must have at least one lane") };
+        let mut lanes = [0; L];
+        lanes[0] = x as u64;
+        match lanes.get_mut(1) {
+            Some(hi) => *hi = (x >> 64) as u64,
+            None if x >> 64 != 0 => return None,
+            None => {}
+        }
+        Some(Lanes(lanes))
+    }
+
+    fn overflowing_add(self, rhs: Self) -> (Self, bool) {
+        let mut out = [0; L];
+        let mut carry = false;
+        for (o, (a, b)) in out.iter_mut().zip(self.0.iter().zip(&rhs.0)) {
+            let (s, c1) = a.overflowing_add(*b);
+            let (s, c2) = s.overflowing_add(carry as u64);
+            *o = s;
+            carry = c1 || c2;
+        }
+        (Lanes(out), carry)
+    }
+
+    fn overflowing_sub(self, rhs: Self) -> (Self, bool) {
+        let mut out = [0; L];
+        let mut borrow = false;
+        for (o, (a, b)) in out.iter_mut().zip(self.0.iter().zip(&rhs.0)) {
+            let (d, b1) = a.overflowing_sub(*b);
+            let (d, b2) = d.overflowing_sub(borrow as u64);
+            *o = d;
+            borrow = b1 || b2;
+        }
+        (Lanes(out), borrow)
+    }
+
+    fn overflowing_mul(self, rhs: Self) -> (Self, bool) {
+        let mut out = [0; L];
+        let mut overflow = false;
+        for i in 0..L {
+            if self.0[i] == 0 {
+                continue;
+            }
+            let mut carry = 0u128;
+            for j in 0..L {
+                let t = self.0[i] as u128 * rhs.0[j] as u128 + carry + out.get(i + j).map_or(0, |&o| o as u128);
+                match out.get_mut(i + j) {
+                    Some(o) => *o = t as u64,
+                    None => overflow |= t as u64 != 0,
+                }
+                carry = t >> 64;
+            }
+            overflow |= carry != 0;
+        }
+        (Lanes(out), overflow)
+    }
+
+    fn div_rem(self, rhs: Self) -> (Self, Self) {
+        assert!(!rhs.is_zero(), "attempt to divide by zero");
+        let mut q = Self::ZERO;
+        let mut r = Self::ZERO;
+        for i in (0..Self::BITS - self.leading_zeros() as usize).rev() {
+            let carry = r.0[L - 1] >> 63 == 1;
+            r <<= 1;
+            r.0[0] |= self.0[i / 64] >> (i % 64) & 1;
+            if carry || r >= rhs {
+                r = r.overflowing_sub(rhs).0;
+                q.0[i / 64] |= 1 << (i % 64);
+            }
+        }
+        (q, r)
+    }
+
+    fn fmt_radix(&self, f: &mut fmt::Formatter<'_>, prefix: &str, lane: fn(u64, bool) -> String) -> fmt::Result {
+        let mut s = String::new();
+        match self.0.iter().rposition(|&x| x != 0) {
+            Some(hi) => {
+                s.push_str(&lane(self.0[hi], false));
+                for &x in self.0[..hi].iter().rev() {
+                    s.push_str(&lane(x, true));
+                }
+            }
+            None => s.push('0'),
+        }
+        f.pad_integral(true, prefix, &s)
+    }
+}
+
+impl<const L: usize> Default for Lanes<L> {
+    #[inline]
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl<const L: usize> From<[u64; L]> for Lanes<L> {
+    #[inline]
+    fn from(lanes: [u64; L]) -> Self {
+        const { assert!(L > 0, "Lanes must have at least one lane") };
+        Lanes(lanes)
+    }
+}
+
+impl<const L: usize> From<Lanes<L>> for [u64; L] {
+    #[inline]
+    fn from(w: Lanes<L>) -> Self {
+        w.0
+    }
+}
+
+impl<const L: usize> Ord for Lanes<L> {
+    #[inline]
+    fn cmp(&self, that: &Self) -> Ordering {
+        self.0.iter().rev().cmp(that.0.iter().rev())
+    }
+}
+
+impl<const L: usize> PartialOrd for Lanes<L> {
+    #[inline]
+    fn partial_cmp(&self, that: &Self) -> Option<Ordering> {
+        Some(self.cmp(that))
+    }
+}
+
+impl<const L: usize> fmt::Display for Lanes<L> {
+    /// ```
+    /// # use bits_core::word::{Lanes, U256};
+    /// assert_eq!(Lanes([0, 1]).to_string(), "18446744073709551616");
+    /// assert_eq!(format!("{:>5}", U256::from([42, 0, 0, 0])), "   42");
+    /// assert_eq!(U256::default().to_string(), "0");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const TEN19: u64 = 10_000_000_000_000_000_000;
+        let ten19 = Self::from_u128(TEN19 as u128).unwrap_or(Self::ZERO);
+        let mut chunks = Vec::new();
+        let mut rest = *self;
+        if L == 1 {
+            chunks.push(rest.0[0]);
+        } else {
+            loop {
+                let (q, r) = rest.div_rem(ten19);
+                chunks.push(r.0[0]);
+                if q.is_zero() {
+                    break;
+                }
+                rest = q;
+            }
+        }
+        let mut s = String::new();
+        let mut chunks = chunks.into_iter().rev();
+        write!(s, "{}", chunks.next().unwrap_or(0))?;
+        for c in chunks {
+            write!(s, "{:019}", c)?;
+        }
+        f.pad_integral(true, "", &s)
+    }
+}
+
+impl<const L: usize> fmt::Debug for Lanes<L> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl<const L: usize> fmt::Binary for Lanes<L> {
+    /// ```
+    /// # use bits_core::word::Lanes;
+    /// assert_eq!(format!("{:#b}", Lanes([0b101, 0b1])), format!("0b1{:064b}", 0b101));
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_radix(f, "0b", |x, pad| if pad { format!("{:064b}", x) } else { format!("{:b}", x) })
+    }
+}
+
+impl<const L: usize> fmt::LowerHex for Lanes<L> {
+    /// ```
+    /// # use bits_core::word::Lanes;
+    /// assert_eq!(format!("{:x}", Lanes([0xabc, 0xf])), "f0000000000000abc");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_radix(f, "0x", |x, pad| if pad { format!("{:016x}", x) } else { format!("{:x}", x) })
+    }
+}
+
+impl<const L: usize> fmt::UpperHex for Lanes<L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_radix(f, "0x", |x, pad| if pad { format!("{:016X}", x) } else { format!("{:X}", x) })
+    }
+}
+
+impl<const L: usize> ops::Not for Lanes<L> {
+    type Output = Self;
+    #[inline]
+    fn not(mut self) -> Self {
+        self.0.iter_mut().for_each(|x| *x = !*x);
+        self
+    }
+}
+
+macro_rules! impl_bitwise_for_lanes {
+    ($( $Op:ident $op:ident $OpAssign:ident $op_assign:ident )*) => ($(
+        impl<const L: usize> ops::$Op for Lanes<L> {
+            type Output = Self;
+            #[inline]
+            fn $op(mut self, that: Self) -> Self {
+                ops::$OpAssign::$op_assign(&mut self, that);
+                self
+            }
+        }
+
+        impl<const L: usize> ops::$OpAssign for Lanes<L> {
+            #[inline]
+            fn $op_assign(&mut self, that: Self) {
+                for (a, b) in self.0.iter_mut().zip(that.0) {
+                    ops::$OpAssign::$op_assign(a, b);
+                }
+            }
+        }
+    )*)
+}
+impl_bitwise_for_lanes!(
+    BitAnd bitand BitAndAssign bitand_assign
+    BitOr bitor BitOrAssign bitor_assign
+    BitXor bitxor BitXorAssign bitxor_assign
+);
+
+impl<const L: usize> ops::Shl<usize> for Lanes<L> {
+    type Output = Self;
+    fn shl(self, n: usize) -> Self {
+        if n >= Self::BITS {
+            return Self::ZERO;
+        }
+        let (q, r) = (n / 64, n % 64);
+        let mut out = [0; L];
+        for (i, o) in out.iter_mut().enumerate().skip(q) {
+            *o = self.0[i - q] << r;
+            if r > 0 && i > q {
+                *o |= self.0[i - q - 1] >> (64 - r);
+            }
+        }
+        Lanes(out)
+    }
+}
+
+impl<const L: usize> ops::Shr<usize> for Lanes<L> {
+    type Output = Self;
+    fn shr(self, n: usize) -> Self {
+        if n >= Self::BITS {
+            return Self::ZERO;
+        }
+        let (q, r) = (n / 64, n % 64);
+        let mut out = [0; L];
+        for (i, o) in out.iter_mut().enumerate().take(L - q) {
+            *o = self.0[i + q] >> r;
+            if r > 0 && i + q + 1 < L {
+                *o |= self.0[i + q + 1] << (64 - r);
+            }
+        }
+        Lanes(out)
+    }
+}
+
+impl<const L: usize> ops::ShlAssign<usize> for Lanes<L> {
+    #[inline]
+    fn shl_assign(&mut self, n: usize) {
+        *self = *self << n;
+    }
+}
+
+impl<const L: usize> ops::ShrAssign<usize> for Lanes<L> {
+    #[inline]
+    fn shr_assign(&mut self, n: usize) {
+        *self = *self >> n;
+    }
+}
+
+impl<const L: usize> ops::Add for Lanes<L> {
+    type Output = Self;
+    #[inline]
+    fn add(self, that: Self) -> Self {
+        self.overflowing_add(that).0
+    }
+}
+
+impl<const L: usize> ops::Sub for Lanes<L> {
+    type Output = Self;
+    #[inline]
+    fn sub(self, that: Self) -> Self {
+        self.overflowing_sub(that).0
+    }
+}
+
+impl<const L: usize> ops::Mul for Lanes<L> {
+    type Output = Self;
+    #[inline]
+    fn mul(self, that: Self) -> Self {
+        self.overflowing_mul(that).0
+    }
+}
+
+impl<const L: usize> ops::Div for Lanes<L> {
+    type Output = Self;
+    #[inline]
+    fn div(self, that: Self) -> Self {
+        self.div_rem(that).0
+    }
+}
+
+impl<const L: usize> ops::Rem for Lanes<L> {
+    type Output = Self;
+    #[inline]
+    fn rem(self, that: Self) -> Self {
+        self.div_rem(that).1
+    }
+}
+
+impl<const L: usize> Zero for Lanes<L> {
+    #[inline]
+    fn zero() -> Self {
+        Self::ZERO
+    }
+    #[inline]
+    fn is_zero(&self) -> bool {
+        self.0.iter().all(|&x| x == 0)
+    }
+}
+
+impl<const L: usize> One for Lanes<L> {
+    #[inline]
+    fn one() -> Self {
+        Self::ONE
+    }
+}
+
+impl<const L: usize> Num for Lanes<L> {
+    type FromStrRadixErr = ParseLanesError;
+
+    /// ```
+    /// # use num::Num;
+    /// # use bits_core::word::Lanes;
+    /// assert_eq!(Lanes::<2>::from_str_radix("18446744073709551617", 10), Ok(Lanes([1, 1])));
+    /// assert!(Lanes::<1>::from_str_radix("18446744073709551616", 10).is_err());
+    /// ```
+    fn from_str_radix(s: &str, radix: u32) -> Result<Self, ParseLanesError> {
+        let err = ParseLanesError(());
+        let base = Self::from_u128(radix as u128).ok_or(err.clone())?;
+        if s.is_empty() {
+            return Err(err);
+        }
+        s.chars().try_fold(Self::ZERO, |acc, c| {
+            let d = c.to_digit(radix).and_then(|d| Self::from_u128(d as u128));
+            d.and_then(|d| acc.checked_mul(&base)?.checked_add(&d)).ok_or(err.clone())
+        })
+    }
+}
+
+impl<const L: usize> Bounded for Lanes<L> {
+    #[inline]
+    fn min_value() -> Self {
+        Self::ZERO
+    }
+    #[inline]
+    fn max_value() -> Self {
+        Self::MAX
+    }
+}
+
+impl<const L: usize> ToPrimitive for Lanes<L> {
+    #[inline]
+    fn to_i64(&self) -> Option<i64> {
+        self.to_u64().and_then(|x| x.to_i64())
+    }
+    #[inline]
+    fn to_u64(&self) -> Option<u64> {
+        const { assert!(L > 0, "Lanes must have at least one lane") };
+        self.0[1..].iter().all(|&x| x == 0).then_some(self.0[0])
+    }
+    #[inline]
+    fn to_i128(&self) -> Option<i128> {
+        self.to_u128().and_then(|x| x.to_i128())
+    }
+    #[inline]
+    fn to_u128(&self) -> Option<u128> {
+        const { assert!(L > 0, "Lanes must have at least one lane") };
+        let hi = self.0.get(1).copied().unwrap_or(0);
+        self.0.iter().skip(2).all(|&x| x == 0).then_some((hi as u128) << 64 | self.0[0] as u128)
+    }
+}
+
+impl<const L: usize> NumCast for Lanes<L> {
+    #[inline]
+    fn from<T: ToPrimitive>(n: T) -> Option<Self> {
+        n.to_u128().and_then(Self::from_u128)
+    }
+}
+
+impl<const L: usize> CheckedAdd for Lanes<L> {
+    #[inline]
+    fn checked_add(&self, that: &Self) -> Option<Self> {
+        let (x, overflow) = self.overflowing_add(*that);
+        (!overflow).then_some(x)
+    }
+}
+
+impl<const L: usize> CheckedSub for Lanes<L> {
+    #[inline]
+    fn checked_sub(&self, that: &Self) -> Option<Self> {
+        let (x, overflow) = self.overflowing_sub(*that);
+        (!overflow).then_some(x)
+    }
+}
+
+impl<const L: usize> CheckedMul for Lanes<L> {
+    #[inline]
+    fn checked_mul(&self, that: &Self) -> Option<Self> {
+        let (x, overflow) = self.overflowing_mul(*that);
+        (!overflow).then_some(x)
+    }
+}
+
+impl<const L: usize> CheckedDiv for Lanes<L> {
+    #[inline]
+    fn checked_div(&self, that: &Self) -> Option<Self> {
+        (!that.is_zero()).then(|| self.div_rem(*that).0)
+    }
+}
+
+impl<const L: usize> Saturating for Lanes<L> {
+    #[inline]
+    fn saturating_add(self, that: Self) -> Self {
+        self.checked_add(&that).unwrap_or(Self::MAX)
+    }
+    #[inline]
+    fn saturating_sub(self, that: Self) -> Self {
+        self.checked_sub(&that).unwrap_or(Self::ZERO)
+    }
+}
+
+impl<const L: usize> PrimInt for Lanes<L> {
+    #[inline]
+    fn count_ones(self) -> u32 {
+        self.0.iter().map(|x| x.count_ones()).sum()
+    }
+
+    #[inline]
+    fn count_zeros(self) -> u32 {
+        self.0.iter().map(|x| x.count_zeros()).sum()
+    }
+
+    #[inline]
+    fn leading_zeros(self) -> u32 {
+        match self.0.iter().rposition(|&x| x != 0) {
+            Some(i) => ((L - 1 - i) * 64) as u32 + self.0[i].leading_zeros(),
+            None => Self::BITS as u32,
+        }
+    }
+
+    #[inline]
+    fn trailing_zeros(self) -> u32 {
+        match self.0.iter().position(|&x| x != 0) {
+            Some(i) => (i * 64) as u32 + self.0[i].trailing_zeros(),
+            None => Self::BITS as u32,
+        }
+    }
+
+    #[inline]
+    fn rotate_left(self, n: u32) -> Self {
+        let n = n as usize % Self::BITS;
+        if n == 0 {
+            self
+        } else {
+            (self << n) | (self >> (Self::BITS - n))
+        }
+    }
+
+    #[inline]
+    fn rotate_right(self, n: u32) -> Self {
+        let n = n as usize % Self::BITS;
+        self.rotate_left((Self::BITS - n) as u32)
+    }
+
+    #[inline]
+    fn signed_shl(self, n: u32) -> Self {
+        self << n as usize
+    }
+
+    #[inline]
+    fn signed_shr(self, n: u32) -> Self {
+        let n = n as usize;
+        if self.0[L - 1] >> 63 == 1 {
+            (self >> n) | !(Self::MAX >> n)
+        } else {
+            self >> n
+        }
+    }
+
+    #[inline]
+    fn unsigned_shl(self, n: u32) -> Self {
+        self << n as usize
+    }
+
+    #[inline]
+    fn unsigned_shr(self, n: u32) -> Self {
+        self >> n as usize
+    }
+
+    #[inline]
+    fn swap_bytes(self) -> Self {
+        let mut out = self.0;
+        out.reverse();
+        out.iter_mut().for_each(|x| *x = x.swap_bytes());
+        Lanes(out)
+    }
+
+    #[inline]
+    fn from_be(x: Self) -> Self {
+        x.to_be()
+    }
+
+    #[inline]
+    fn from_le(x: Self) -> Self {
+        x.to_le()
+    }
+
+    #[inline]
+    fn to_be(self) -> Self {
+        if cfg!(target_endian = "big") {
+            self
+        } else {
+            self.swap_bytes()
+        }
+    }
+
+    #[inline]
+    fn to_le(self) -> Self {
+        if cfg!(target_endian = "little") {
+            self
+        } else {
+            self.swap_bytes()
+        }
+    }
+
+    fn pow(self, mut exp: u32) -> Self {
+        let mut base = self;
+        let mut acc = Self::ONE;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                acc = acc * base;
+            }
+            base = base * base;
+            exp >>= 1;
+        }
+        acc
+    }
+}
+
+impl<const L: usize> Word for Lanes<L> {
+    const _0: Self = Self::ZERO;
+
+    const _1: Self = Self::ONE;
+
+    const BITS_MINUS_1: u32 = (Self::BITS - 1) as u32;
+
+    #[inline]
+    fn lsb(self) -> Self {
+        let mut out = Self::ZERO;
+        if let Some(i) = self.0.iter().position(|&x| x != 0) {
+            out.0[i] = self.0[i].lsb();
+        }
+        out
+    }
+
+    #[inline]
+    fn msb(self) -> Self {
+        let mut out = Self::ZERO;
+        if let Some(i) = self.0.iter().rposition(|&x| x != 0) {
+            out.0[i] = self.0[i].msb();
+        }
+        out
+    }
+}
+
+impl<const L: usize> Block for Lanes<L> {
+    const BITS: usize = {
+        assert!(L > 0, "Lanes must have at least one lane");
+        64 * L
+    };
+
+    #[inline]
+    fn empty() -> Self {
+        Self::ZERO
+    }
+
+    #[inline]
+    fn test(&self, i: usize) -> Option<bool> {
+        Bits::new(&self.0).test(i)
+    }
+}
+
+impl<const L: usize> BlockMut for Lanes<L> {
+    #[inline]
+    fn set1(&mut self, i: usize) {
+        Bits::new_mut(&mut self.0).set1(i)
+    }
+    #[inline]
+    fn set0(&mut self, i: usize) {
+        Bits::new_mut(&mut self.0).set0(i)
+    }
+}
+
+impl<const L: usize> Count for Lanes<L> {
+    #[inline]
+    fn count1(&self) -> usize {
+        self.count_ones() as usize
+    }
+
+    #[inline]
+    fn count0(&self) -> usize {
+        self.count_zeros() as usize
+    }
+
+    #[inline]
+    fn all(&self) -> bool {
+        self.0.iter().all(|&x| x == !0)
+    }
+
+    #[inline]
+    fn any(&self) -> bool {
+        !self.is_zero()
+    }
+}
+
+impl<const L: usize> Rank for Lanes<L> {
+    #[inline]
+    fn rank1<R: RangeBounds<usize>>(&self, r: R) -> usize {
+        Bits::new(&self.0).rank1(r)
+    }
+
+    #[inline]
+    fn rank0<R: RangeBounds<usize>>(&self, r: R) -> usize {
+        Bits::new(&self.0).rank0(r)
+    }
+}
+
+impl<const L: usize> Select for Lanes<L> {
+    #[inline]
+    fn select1(&self, n: usize) -> Option<usize> {
+        Bits::new(&self.0).select1(n)
+    }
+
+    #[inline]
+    fn select0(&self, n: usize) -> Option<usize> {
+        Bits::new(&self.0).select0(n)
+    }
+}
+
+impl<const L: usize> Pack for Lanes<L> {
+    #[inline]
+    fn pack<T: Word>(&mut self, i: usize, n: usize, bits: T) {
+        debug_assert!(i < Self::BITS && n <= T::BITS);
+        Bits::new_mut(&mut self.0).pack(i, n, bits)
+    }
+
+    #[inline]
+    fn unpack<T: Word>(&self, i: usize, n: usize) -> T {
+        Bits::new(&self.0).unpack(i, n)
+    }
+}
+
+impl<const L: usize> helper::Assign<Lanes<L>> for Lanes<L> {
+    #[inline]
+    fn and(a: &mut Self, b: &Lanes<L>) {
+        *a &= *b;
+    }
+    #[inline]
+    fn not(a: &mut Self, b: &Lanes<L>) {
+        *a &= !*b;
+    }
+    #[inline]
+    fn or(a: &mut Self, b: &Lanes<L>) {
+        *a |= *b;
+    }
+    #[inline]
+    fn xor(a: &mut Self, b: &Lanes<L>) {
+        *a ^= *b;
+    }
+}
@@ -0,0 +1,230 @@
+use std::hash::Hash;
+
+use bits_core::mask::helper::Assign;
+use bits_core::{BitVec, BitsError, SpaceUsage};
+
+use crate::Reader;
+
+/// A Bloom filter of `m` bits with `k` hash functions.
+///
+/// The `k` positions of a value are derived from a single 64-bit hash by double hashing,
+/// `h1 + i * h2 mod m`. Filters of the same shape can be combined by [`Assign`]:
+/// `or` gives the union, and `and` gives a filter which contains every value in both
+/// (with a false positive rate no better than that of either operand).
+/// Only `or` and `and` keep the result a Bloom filter; `not` and `xor` clear bits
+/// of inserted values, so the result may have false negatives.
+///
+/// # Examples
+///
+/// ```
+/// # use bits_filter::Bloom;
+/// let mut bloom = Bloom::with_rate(1000, 0.01);
+/// bloom.insert("apple");
+/// bloom.insert(&42u64);
+/// assert!(bloom.contains("apple"));
+/// assert!(bloom.contains(&42u64));
+///
+/// let decoded = Bloom::from_bytes(&bloom.to_bytes()).unwrap();
+/// assert_eq!(decoded, bloom);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bloom {
+    bits: BitVec<u64>,
+    m: usize,
+    k: usize,
+}
+
+impl Bloom {
+    /// Constructs an empty filter of `m` bits and `k` hash functions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `m` or `k` is zero.
+    pub fn new(m: usize, k: usize) -> Bloom {
+        assert!(m > 0 && k > 0, "m and k must be positive");
+        Bloom { bits: BitVec::new(m), m, k }
+    }
+
+    /// Constructs an empty filter sized for `n` values at a false positive rate of `rate`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` is not in `(0, 1)`.
+    ///
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_filter::Bloom;
+    /// let bloom = Bloom::with_rate(1000, 0.01);
+    /// assert_eq!((bloom.bits(), bloom.k()), (9586, 7));
+    /// let bloom = Bloom::with_rate(0, 0.5);
+    /// assert_eq!((bloom.bits(), bloom.k()), (1, 1));
+    /// ```
+    pub fn with_rate(n: usize, rate: f64) -> Bloom {
+        assert!(rate > 0.0 && rate < 1.0, "rate must be in (0, 1), got {rate}");
+        let ln2 = std::f64::consts::LN_2;
+        let m = (-(n as f64) * rate.ln() / (ln2 * ln2)).ceil().max(1.0);
+        let k = (m / n.max(1) as f64 * ln2).round().max(1.0);
+        Bloom::new(m as usize, k as usize)
+    }
+
+    /// Returns the number of bits `m`.
+    #[inline]
+    pub fn bits(&self) -> usize {
+        self.m
+    }
+
+    /// Returns the number of hash functions `k`.
+    #[inline]
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    /// Returns the number of bits set.
+    #[inline]
+    pub fn count1(&self) -> usize {
+        self.bits.count1()
+    }
+
+    /// Returns true if no values have been inserted.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        !self.bits.any()
+    }
+
+    /// Removes all values.
+    pub fn clear(&mut self) {
+        self.bits.as_mut_slice().fill(0);
+    }
+
+    fn positions(&self, h: u64) -> impl Iterator<Item = usize> {
+        let m = self.m as u64;
+        let h2 = crate::mix(h) | 1;
+        (0..self.k as u64).map(move |i| (h.wrapping_add(i.wrapping_mul(h2)) % m) as usize)
+    }
+
+    pub fn insert<T: ?Sized + Hash>(&mut self, x: &T) {
+        for p in self.positions(crate::hash(x)) {
+            self.bits.set1(p);
+        }
+    }
+
+    /// Returns false if `x` has never been inserted, true if it probably has.
+    pub fn contains<T: ?Sized + Hash>(&self, x: &T) -> bool {
+        self.positions(crate::hash(x)).all(|p| self.bits.test(p) == Some(true))
+    }
+
+    /// Estimates the number of distinct values inserted, from the number of bits set.
+    ///
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_filter::Bloom;
+    /// let mut bloom = Bloom::with_rate(10_000, 0.01);
+    /// assert_eq!(bloom.estimate(), 0);
+    /// for i in 0..5000 {
+    ///     bloom.insert(&i);
+    /// }
+    /// assert!((4900..5100).contains(&bloom.estimate()));
+    /// ```
+    pub fn estimate(&self) -> usize {
+        let (m, k, x) = (self.m as f64, self.k as f64, self.count1() as f64);
+        if x >= m {
+            return usize::MAX;
+        }
+        (-m / k * (1.0 - x / m).ln()).round() as usize
+    }
+
+    /// Adds all values in `that` to `self`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `m` or `k` differ.
+    #[inline]
+    pub fn union(&mut self, that: &Bloom) {
+        Assign::or(self, that);
+    }
+
+    /// Keeps only bits which are also set in `that`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `m` or `k` differ.
+    #[inline]
+    pub fn intersect(&mut self, that: &Bloom) {
+        Assign::and(self, that);
+    }
+
+    /// Encodes the filter as `m`, `k` and the bits, each word in little endian.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let words = self.bits.as_slice();
+        let mut buf = Vec::with_capacity(8 * (2 + words.len()));
+        buf.extend_from_slice(&(self.m as u64).to_le_bytes());
+        buf.extend_from_slice(&(self.k as u64).to_le_bytes());
+        for w in words {
+            buf.extend_from_slice(&w.to_le_bytes());
+        }
+        buf
+    }
+
+    /// Decodes a filter encoded by [`Bloom::to_bytes`].
+    ///
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_core::BitsError;
+    /// # use bits_filter::Bloom;
+    /// let bytes = Bloom::new(100, 3).to_bytes();
+    /// assert!(Bloom::from_bytes(&bytes).is_ok());
+    /// assert_eq!(Bloom::from_bytes(&bytes[..20]), Err(BitsError::InvalidBytes));
+    /// assert_eq!(Bloom::from_bytes(&Bloom::new(100, 3).to_bytes()[..8]), Err(BitsError::InvalidBytes));
+    ///
+    /// // A bit past `m` is set.
+    /// let mut bytes = bytes;
+    /// *bytes.last_mut().unwrap() = 0x80;
+    /// assert_eq!(Bloom::from_bytes(&bytes), Err(BitsError::InvalidBytes));
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<Bloom, BitsError> {
+        let mut r = Reader(bytes);
+        let m = r.usize()?;
+        let k = r.usize()?;
+        if m == 0 || k == 0 {
+            return Err(BitsError::InvalidBytes);
+        }
+        let words = r.words(bit::blocks(m, 64))?;
+        if m % 64 != 0 && words[words.len() - 1] >> (m % 64) != 0 {
+            return Err(BitsError::InvalidBytes);
+        }
+        Ok(Bloom { bits: BitVec::from(words), m, k })
+    }
+
+    fn check_shape(&self, that: &Bloom) {
+        assert!(self.m == that.m && self.k == that.k, "filters must have the same m and k");
+    }
+}
+
+impl Assign<Bloom> for Bloom {
+    fn and(this: &mut Self, that: &Bloom) {
+        this.check_shape(that);
+        Assign::and(&mut *this.bits, &*that.bits);
+    }
+    fn not(this: &mut Self, that: &Bloom) {
+        this.check_shape(that);
+        Assign::not(&mut *this.bits, &*that.bits);
+    }
+    fn or(this: &mut Self, that: &Bloom) {
+        this.check_shape(that);
+        Assign::or(&mut *this.bits, &*that.bits);
+    }
+    fn xor(this: &mut Self, that: &Bloom) {
+        this.check_shape(that);
+        Assign::xor(&mut *this.bits, &*that.bits);
+    }
+}
+
+impl SpaceUsage for Bloom {
+    #[inline]
+    fn heap_bytes(&self) -> usize {
+        self.bits.heap_bytes()
+    }
+}
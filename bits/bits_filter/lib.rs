@@ -0,0 +1,95 @@
+//! `bits_filter`
+//!
+//! Approximate membership filters backed by [`BitVec`](bits_core::BitVec).
+//!
+//! Both filters hash values with a fixed hash function which writes integers in little endian,
+//! but the bytes fed to it come from [`Hash`] impls, which are not guaranteed to be stable
+//! across Rust versions (`str` and derived impls included). A filter encoded by `to_bytes`
+//! is valid only for programs built with the same toolchain as the one which encoded it.
+
+mod bloom;
+mod quotient;
+
+use std::hash::{Hash, Hasher};
+
+use bits_core::BitsError;
+
+pub use bloom::Bloom;
+pub use quotient::QuotientFilter;
+
+/// Hashes `x` into 64 bits.
+fn hash<T: ?Sized + Hash>(x: &T) -> u64 {
+    let mut h = Fnv1a::default();
+    x.hash(&mut h);
+    h.finish()
+}
+
+/// Scrambles `x`, used to derive the second hash for double hashing.
+#[inline]
+fn mix(mut x: u64) -> u64 {
+    x ^= x >> 33;
+    x = x.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    x ^= x >> 33;
+    x = x.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    x ^ x >> 33
+}
+
+/// FNV-1a, which writes integers in little endian.
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn finish(&self) -> u64 {
+        mix(self.0)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 = (self.0 ^ b as u64).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes())
+    }
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes())
+    }
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes())
+    }
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes())
+    }
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64)
+    }
+}
+
+/// Reads little endian `u64`s from the front of bytes.
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn u64(&mut self) -> Result<u64, BitsError> {
+        let (head, rest) = self.0.split_first_chunk::<8>().ok_or(BitsError::InvalidBytes)?;
+        self.0 = rest;
+        Ok(u64::from_le_bytes(*head))
+    }
+
+    fn usize(&mut self) -> Result<usize, BitsError> {
+        usize::try_from(self.u64()?).map_err(|_| BitsError::InvalidBytes)
+    }
+
+    /// Reads exactly `n` words, and fails if any bytes remain.
+    fn words(mut self, n: usize) -> Result<Vec<u64>, BitsError> {
+        if self.0.len() / 8 != n || self.0.len() % 8 != 0 {
+            return Err(BitsError::InvalidBytes);
+        }
+        (0..n).map(|_| self.u64()).collect()
+    }
+}
@@ -0,0 +1,341 @@
+use std::hash::Hash;
+
+use bits_core::{BitVec, BitsError, SpaceUsage};
+
+use crate::Reader;
+
+const OCCUPIED: u64 = 1;
+const CONTINUATION: u64 = 2;
+const SHIFTED: u64 = 4;
+const FLAGS: u64 = OCCUPIED | CONTINUATION | SHIFTED;
+
+/// A counting quotient filter of `2^q` slots with `r`-bit remainders.
+///
+/// The lower `q + r` bits of a hash are split into a quotient, the slot the value belongs to,
+/// and a remainder stored in that slot, or in a later one after shifting as in linear probing.
+/// Each slot takes `r + 3` bits, packed into a `BitVec<u64>`: three flags and the remainder.
+/// Inserting the same value twice stores its remainder twice,
+/// so that values can be counted and removed.
+///
+/// One slot is always kept empty, so at most `2^q - 1` values can be stored.
+///
+/// # Examples
+///
+/// ```
+/// # use bits_filter::QuotientFilter;
+/// let mut qf = QuotientFilter::new(10, 8);
+/// qf.insert("apple").unwrap();
+/// qf.insert("apple").unwrap();
+/// qf.insert("pear").unwrap();
+/// assert_eq!(qf.len(), 3);
+/// assert_eq!(qf.count("apple"), 2);
+/// assert!(qf.contains("pear"));
+///
+/// assert!(qf.remove("apple"));
+/// assert_eq!(qf.count("apple"), 1);
+///
+/// let decoded = QuotientFilter::from_bytes(&qf.to_bytes()).unwrap();
+/// assert_eq!(decoded, qf);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuotientFilter {
+    slots: BitVec<u64>,
+    q: usize,
+    r: usize,
+    len: usize,
+}
+
+impl QuotientFilter {
+    /// Constructs an empty filter of `2^q` slots with `r`-bit remainders.
+    ///
+    /// The false positive rate is about `2^-r` when the filter is full.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `q` or `r` is zero, `q + r > 64`, `r + 3 > 64`, or `q >= usize::BITS`.
+    /// A slot of `r + 3` bits must fit in a `u64`, so `r` is at most 61.
+    pub fn new(q: usize, r: usize) -> QuotientFilter {
+        assert!(q > 0 && r > 0 && q + r <= 64, "q and r must be positive, and q + r <= 64");
+        assert!(r + 3 <= 64, "r must be at most 61");
+        assert!(q < usize::BITS as usize, "q is too large");
+        QuotientFilter { slots: BitVec::new((1 << q) * (r + 3)), q, r, len: 0 }
+    }
+
+    /// Returns the number of values stored.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the maximum number of values, `2^q - 1`.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        (1 << self.q) - 1
+    }
+
+    /// Removes all values.
+    pub fn clear(&mut self) {
+        self.slots.as_mut_slice().fill(0);
+        self.len = 0;
+    }
+
+    #[inline]
+    fn width(&self) -> usize {
+        self.r + 3
+    }
+
+    #[inline]
+    fn mask(&self) -> usize {
+        (1 << self.q) - 1
+    }
+
+    #[inline]
+    fn next(&self, i: usize) -> usize {
+        (i + 1) & self.mask()
+    }
+
+    #[inline]
+    fn prev(&self, i: usize) -> usize {
+        i.wrapping_sub(1) & self.mask()
+    }
+
+    #[inline]
+    fn slot(&self, i: usize) -> u64 {
+        self.slots.unpack::<u64>(i * self.width(), self.width())
+    }
+
+    #[inline]
+    fn set_slot(&mut self, i: usize, slot: u64) {
+        let w = self.width();
+        self.slots.pack(i * w, w, slot);
+    }
+
+    #[inline]
+    fn is_set(&self, i: usize, flag: u64) -> bool {
+        self.slot(i) & flag != 0
+    }
+
+    #[inline]
+    fn is_vacant(&self, i: usize) -> bool {
+        self.slot(i) & FLAGS == 0
+    }
+
+    /// Splits the hash of `x` into a quotient and a remainder.
+    fn fingerprint<T: ?Sized + Hash>(&self, x: &T) -> (usize, u64) {
+        let h = crate::hash(x);
+        let fq = (h >> self.r) as usize & self.mask();
+        let fr = h & ((1 << self.r) - 1);
+        (fq, fr)
+    }
+
+    /// Returns the slot at which the run of `fq` starts, or would start if `fq` is not occupied.
+    fn run_start(&self, fq: usize) -> usize {
+        // Walk back to the start of the cluster, which is never shifted.
+        let mut b = fq;
+        while self.is_set(b, SHIFTED) {
+            b = self.prev(b);
+        }
+        // Walk forward, skipping a run for each occupied quotient before `fq`.
+        let mut s = b;
+        while b != fq {
+            loop {
+                s = self.next(s);
+                if !self.is_set(s, CONTINUATION) {
+                    break;
+                }
+            }
+            loop {
+                b = self.next(b);
+                if self.is_set(b, OCCUPIED) {
+                    break;
+                }
+            }
+        }
+        s
+    }
+
+    /// Returns the remainders in the run of `fq`, with the slot of each.
+    fn run(&self, fq: usize) -> impl Iterator<Item = (usize, u64)> + '_ {
+        let start = self.is_set(fq, OCCUPIED).then(|| self.run_start(fq));
+        let mut next = start;
+        std::iter::from_fn(move || {
+            let s = next?;
+            let n = self.next(s);
+            next = self.is_set(n, CONTINUATION).then_some(n);
+            Some((s, self.slot(s) >> 3))
+        })
+    }
+
+    /// Returns false if `x` has never been inserted, true if it probably has.
+    pub fn contains<T: ?Sized + Hash>(&self, x: &T) -> bool {
+        let (fq, fr) = self.fingerprint(x);
+        self.run(fq).any(|(_, r)| r == fr)
+    }
+
+    /// Returns how many times `x` has probably been inserted and not removed.
+    /// This can overcount because of false positives, but never undercounts.
+    pub fn count<T: ?Sized + Hash>(&self, x: &T) -> usize {
+        let (fq, fr) = self.fingerprint(x);
+        self.run(fq).filter(|&(_, r)| r == fr).count()
+    }
+
+    /// Inserts `x`, or returns [`BitsError::Overflow`] if the filter is full.
+    pub fn insert<T: ?Sized + Hash>(&mut self, x: &T) -> Result<(), BitsError> {
+        if self.len == self.capacity() {
+            return Err(BitsError::Overflow);
+        }
+        let (fq, fr) = self.fingerprint(x);
+        self.insert_at(fq, fr);
+        self.len += 1;
+        Ok(())
+    }
+
+    fn insert_at(&mut self, fq: usize, fr: u64) {
+        if self.is_vacant(fq) {
+            self.set_slot(fq, fr << 3 | OCCUPIED);
+            return;
+        }
+
+        let had_run = self.is_set(fq, OCCUPIED);
+        let slot = self.slot(fq);
+        self.set_slot(fq, slot | OCCUPIED);
+
+        // Keep remainders in a run sorted, so that equal ones are adjacent.
+        let start = self.run_start(fq);
+        let mut s = start;
+        if had_run {
+            while self.slot(s) >> 3 <= fr {
+                s = self.next(s);
+                if !self.is_set(s, CONTINUATION) {
+                    break;
+                }
+            }
+        }
+
+        // Shift entries at `s` and after to the right by one, up to the first vacant slot.
+        // Flags other than `OCCUPIED` move with the entries, `OCCUPIED` belongs to the slot.
+        let mut entry = fr << 3 | if had_run && s != start { CONTINUATION } else { 0 };
+        if s != fq {
+            entry |= SHIFTED;
+        }
+        let mut i = s;
+        loop {
+            let old = self.slot(i);
+            let vacant = old & FLAGS == 0;
+            self.set_slot(i, entry | old & OCCUPIED);
+            if vacant {
+                break;
+            }
+            entry = old & !OCCUPIED | SHIFTED;
+            // The old head of the run is now preceded by the new entry.
+            if had_run && i == s && s == start {
+                entry |= CONTINUATION;
+            }
+            i = self.next(i);
+        }
+    }
+
+    /// Removes `x` once, and returns true if its remainder was found.
+    pub fn remove<T: ?Sized + Hash>(&mut self, x: &T) -> bool {
+        let (fq, fr) = self.fingerprint(x);
+        if !self.run(fq).any(|(_, r)| r == fr) {
+            return false;
+        }
+
+        // Take the whole cluster out, and put it back without the removed entry.
+        let mut b = fq;
+        while self.is_set(b, SHIFTED) {
+            b = self.prev(b);
+        }
+        let mut entries = Vec::new();
+        let (mut s, mut quotient) = (b, b);
+        while !self.is_vacant(s) {
+            if s != b && !self.is_set(s, CONTINUATION) {
+                loop {
+                    quotient = self.next(quotient);
+                    if self.is_set(quotient, OCCUPIED) {
+                        break;
+                    }
+                }
+            }
+            entries.push((quotient, self.slot(s) >> 3));
+            s = self.next(s);
+        }
+        let mut i = b;
+        while i != s {
+            self.set_slot(i, 0);
+            i = self.next(i);
+        }
+
+        let found = entries.iter().position(|&e| e == (fq, fr)).expect("the entry must be in the cluster");
+        entries.remove(found);
+        for (q, r) in entries {
+            self.insert_at(q, r);
+        }
+        self.len -= 1;
+        true
+    }
+
+    /// Encodes the filter as `q`, `r`, the number of values and the slots,
+    /// each word in little endian.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let words = self.slots.as_slice();
+        let mut buf = Vec::with_capacity(8 * (3 + words.len()));
+        for n in [self.q, self.r, self.len] {
+            buf.extend_from_slice(&(n as u64).to_le_bytes());
+        }
+        for w in words {
+            buf.extend_from_slice(&w.to_le_bytes());
+        }
+        buf
+    }
+
+    /// Decodes a filter encoded by [`QuotientFilter::to_bytes`].
+    ///
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_core::BitsError;
+    /// # use bits_filter::QuotientFilter;
+    /// let mut qf = QuotientFilter::new(4, 4);
+    /// qf.insert(&1).unwrap();
+    /// let bytes = qf.to_bytes();
+    /// assert_eq!(QuotientFilter::from_bytes(&bytes), Ok(qf));
+    /// assert_eq!(QuotientFilter::from_bytes(&bytes[..bytes.len() - 1]), Err(BitsError::InvalidBytes));
+    ///
+    /// // The number of values does not match the slots.
+    /// let mut bytes = bytes;
+    /// bytes[16] = 2;
+    /// assert_eq!(QuotientFilter::from_bytes(&bytes), Err(BitsError::InvalidBytes));
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<QuotientFilter, BitsError> {
+        let mut reader = Reader(bytes);
+        let q = reader.usize()?;
+        let r = reader.usize()?;
+        let len = reader.usize()?;
+        if q == 0 || r == 0 || q + r > 64 || r + 3 > 64 || q >= usize::BITS as usize {
+            return Err(BitsError::InvalidBytes);
+        }
+        let bits = (1usize << q).checked_mul(r + 3).ok_or(BitsError::InvalidBytes)?;
+        let words = reader.words(bit::blocks(bits, 64))?;
+
+        let qf = QuotientFilter { slots: BitVec::from(words), q, r, len };
+        let used = (0..1 << q).filter(|&i| !qf.is_vacant(i)).count();
+        if used != len || len > qf.capacity() {
+            return Err(BitsError::InvalidBytes);
+        }
+        Ok(qf)
+    }
+}
+
+impl SpaceUsage for QuotientFilter {
+    #[inline]
+    fn heap_bytes(&self) -> usize {
+        self.slots.heap_bytes()
+    }
+}
@@ -0,0 +1,255 @@
+//! `bits_intvec`
+
+use std::iter::FusedIterator;
+use std::ops::Range;
+
+use bits_core::{Bits, SpaceUsage};
+
+/// A vector of unsigned integers, each stored in a fixed number of bits.
+///
+/// Values are packed back to back into `u64` words, so a value may straddle two words
+/// unless the width divides 64. Bits after the last value are always zero.
+///
+/// # Examples
+///
+/// ```
+/// # use bits_intvec::IntVec;
+/// let mut ids = IntVec::new(20);
+/// ids.push(3);
+/// ids.push(1 << 19);
+/// ids.push(12345);
+/// assert_eq!(ids.len(), 3);
+/// assert_eq!(ids.get(1), Some(1 << 19));
+///
+/// ids.set(1, 7);
+/// assert_eq!(ids.iter().collect::<Vec<_>>(), [3, 7, 12345]);
+/// assert_eq!(ids.pop(), Some(12345));
+/// assert_eq!(ids.get(2), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IntVec {
+    data: Vec<u64>,
+    width: usize,
+    len: usize,
+}
+
+/// An iterator over the values of [`IntVec`].
+#[derive(Debug, Clone)]
+pub struct Iter<'a> {
+    vec: &'a IntVec,
+    idx: Range<usize>,
+}
+
+impl IntVec {
+    /// Constructs a new, empty `IntVec` which stores values of `width` bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is not in `1..=64`.
+    pub fn new(width: usize) -> IntVec {
+        Self::with_capacity(width, 0)
+    }
+
+    /// Constructs a new, empty `IntVec` with room for at least `capacity` values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is not in `1..=64`.
+    pub fn with_capacity(width: usize, capacity: usize) -> IntVec {
+        assert!((1..=64).contains(&width), "width must be in 1..=64, got {width}");
+        IntVec { data: Vec::with_capacity(bit::blocks(capacity * width, 64)), width, len: 0 }
+    }
+
+    /// Packs all values of `slice` into a new `IntVec`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is not in `1..=64`, or a value does not fit in `width` bits.
+    ///
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_intvec::IntVec;
+    /// let v = IntVec::from_slice(4, &[1, 2, 3, 15, 0, 9]);
+    /// assert_eq!(v.as_bits().as_slice(), [0x0_9_0_F_3_2_1]);
+    ///
+    /// let v = IntVec::from_slice(33, &[1, 2, 3]);
+    /// assert_eq!(v.iter().collect::<Vec<_>>(), [1, 2, 3]);
+    /// assert_eq!(v.as_bits().bits(), 128);
+    /// ```
+    pub fn from_slice(width: usize, slice: &[u64]) -> IntVec {
+        let mut vec = Self::with_capacity(width, slice.len());
+        if 64 % width == 0 {
+            // No value straddles words, build each word at once.
+            for chunk in slice.chunks(64 / width) {
+                let mut word = 0;
+                for (i, &v) in chunk.iter().enumerate() {
+                    vec.check(v);
+                    word |= v << (i * width);
+                }
+                vec.data.push(word);
+            }
+            vec.len = slice.len();
+        } else {
+            vec.extend(slice.iter().copied());
+        }
+        vec
+    }
+
+    /// Returns the number of bits of each value.
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the number of values.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the largest value that can be stored.
+    #[inline]
+    pub fn max_value(&self) -> u64 {
+        !0 >> (64 - self.width)
+    }
+
+    /// Returns the packed values as bits.
+    #[inline]
+    pub fn as_bits(&self) -> &Bits<u64> {
+        Bits::new(&self.data)
+    }
+
+    /// Returns the `i`-th value, or `None` if out of bounds.
+    #[inline]
+    pub fn get(&self, i: usize) -> Option<u64> {
+        (i < self.len).then(|| self.read(i))
+    }
+
+    /// Overwrites the `i`-th value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of bounds, or `v` does not fit in `width` bits.
+    #[inline]
+    pub fn set(&mut self, i: usize, v: u64) {
+        assert!(i < self.len, "index out of bounds: the len is {} but the index is {i}", self.len);
+        self.check(v);
+        self.write(i, v);
+    }
+
+    /// Appends a value to the back.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `v` does not fit in `width` bits.
+    #[inline]
+    pub fn push(&mut self, v: u64) {
+        self.check(v);
+        self.len += 1;
+        self.data.resize(bit::blocks(self.len * self.width, 64), 0);
+        self.write(self.len - 1, v);
+    }
+
+    /// Removes the last value and returns it, or `None` if empty.
+    #[inline]
+    pub fn pop(&mut self) -> Option<u64> {
+        let v = self.len.checked_sub(1).map(|i| self.read(i))?;
+        self.write(self.len - 1, 0);
+        self.len -= 1;
+        self.data.truncate(bit::blocks(self.len * self.width, 64));
+        Some(v)
+    }
+
+    #[inline]
+    pub fn iter(&self) -> Iter<'_> {
+        Iter { vec: self, idx: 0..self.len }
+    }
+
+    #[inline]
+    fn check(&self, v: u64) {
+        assert!(v <= self.max_value(), "{v} does not fit in {} bits", self.width);
+    }
+
+    #[inline]
+    fn read(&self, i: usize) -> u64 {
+        let (q, r) = bit::addr(i * self.width, 64);
+        if r + self.width <= 64 {
+            (self.data[q] >> r) & self.max_value()
+        } else {
+            self.as_bits().unpack(i * self.width, self.width)
+        }
+    }
+
+    #[inline]
+    fn write(&mut self, i: usize, v: u64) {
+        let (q, r) = bit::addr(i * self.width, 64);
+        if r + self.width <= 64 {
+            let mask = self.max_value() << r;
+            self.data[q] = (self.data[q] & !mask) | (v << r);
+        } else {
+            Bits::new_mut(&mut self.data).pack(i * self.width, self.width, v)
+        }
+    }
+}
+
+impl SpaceUsage for IntVec {
+    #[inline]
+    fn heap_bytes(&self) -> usize {
+        self.data.heap_bytes()
+    }
+}
+
+impl Extend<u64> for IntVec {
+    fn extend<I: IntoIterator<Item = u64>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.data.reserve(bit::blocks(iter.size_hint().0 * self.width, 64));
+        for v in iter {
+            self.push(v);
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a IntVec {
+    type Item = u64;
+    type IntoIter = Iter<'a>;
+    #[inline]
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+impl Iterator for Iter<'_> {
+    type Item = u64;
+
+    #[inline]
+    fn next(&mut self) -> Option<u64> {
+        self.idx.next().map(|i| self.vec.read(i))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.idx.size_hint()
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<u64> {
+        self.idx.nth(n).map(|i| self.vec.read(i))
+    }
+}
+
+impl DoubleEndedIterator for Iter<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<u64> {
+        self.idx.next_back().map(|i| self.vec.read(i))
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+impl FusedIterator for Iter<'_> {}
@@ -0,0 +1,284 @@
+//! `bits_mmap`
+//!
+//! Memory-mapped files viewed as [`Bits`], so that a large bitmap is paged in on demand
+//! instead of being read into memory.
+//!
+//! A file holds words in little endian, back to back from the start of the file.
+//! This is the layout of `Bits<T>` in memory on a little-endian target, so a view is a cast
+//! after checking the length, the alignment and the byte order:
+//!
+//! * the number of bytes must be a multiple of the size of `T`,
+//! * the bytes must be aligned for `T`, which always holds at the start of a mapping,
+//! * `T` must be `u8`, or the target must be little endian.
+//!
+//! The checks fail with [`io::ErrorKind::InvalidData`].
+//!
+//! # Examples
+//!
+//! ```
+//! # use std::io::Write;
+//! # use bits_mmap::Mmap;
+//! let mut file = tempfile::tempfile().unwrap();
+//! for w in [0b1011u64, 0, 1 << 63] {
+//!     file.write_all(&w.to_le_bytes()).unwrap();
+//! }
+//!
+//! let mmap = unsafe { Mmap::map(&file).unwrap() };
+//! let bits = mmap.bits::<u64>().unwrap();
+//! assert_eq!(bits.bits(), 192);
+//! assert_eq!(bits.count1(), 4);
+//!
+//! let pop = mmap.pop::<u64>().unwrap();
+//! assert_eq!(pop.select1(3), Some(191));
+//!
+//! // Bits are in the same order whatever the width of words is.
+//! assert_eq!(mmap.bits::<u8>().unwrap().select1(3), Some(191));
+//! ```
+
+// mmap(2) and munmap(2) are called through libc, so only unix targets are supported.
+#![cfg(unix)]
+
+use std::fs::File;
+use std::ops::{Deref, DerefMut};
+use std::os::unix::io::AsRawFd;
+use std::ptr::{self, NonNull};
+use std::{io, mem, slice};
+
+use bits_aux::Pop;
+use bits_core::Bits;
+
+mod private {
+    /// A word which is valid for any bit pattern, and has the same size on every target.
+    pub trait Plain: bits_core::word::Word {}
+    impl Plain for u8 {}
+    impl Plain for u16 {}
+    impl Plain for u32 {}
+    impl Plain for u64 {}
+    impl Plain for u128 {}
+}
+use private::Plain;
+
+/// A read-only memory map of a whole file.
+#[derive(Debug)]
+pub struct Mmap {
+    map: Map,
+}
+
+/// A writable memory map of a whole file.
+/// Writes are carried through to the file, [`MmapMut::flush`] waits for them.
+#[derive(Debug)]
+pub struct MmapMut {
+    map: Map,
+}
+
+#[derive(Debug)]
+struct Map {
+    ptr: NonNull<u8>,
+    len: usize,
+}
+
+// The mapping is owned, and only reachable through `&` or `&mut` of the owner.
+unsafe impl Send for Map {}
+unsafe impl Sync for Map {}
+
+impl Map {
+    fn new(file: &File, prot: libc::c_int) -> io::Result<Map> {
+        let len = usize::try_from(file.metadata()?.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "file is too large to map"))?;
+        if len == 0 {
+            // mmap fails on an empty range.
+            return Ok(Map { ptr: NonNull::dangling(), len });
+        }
+
+        let ptr = unsafe { libc::mmap(ptr::null_mut(), len, prot, libc::MAP_SHARED, file.as_raw_fd(), 0) };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Map { ptr: NonNull::new(ptr.cast()).expect("mmap must not return null"), len })
+    }
+
+    #[inline]
+    fn as_slice(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl Drop for Map {
+    fn drop(&mut self) {
+        if self.len > 0 {
+            unsafe { libc::munmap(self.ptr.as_ptr().cast(), self.len) };
+        }
+    }
+}
+
+impl Mmap {
+    /// Maps the whole `file`, which must be opened for reading.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated while it is mapped,
+    /// by this process or by others, or the bytes may change under shared references.
+    pub unsafe fn map(file: &File) -> io::Result<Mmap> {
+        Map::new(file, libc::PROT_READ).map(|map| Mmap { map })
+    }
+
+    /// Views the whole file as bits.
+    #[inline]
+    pub fn bits<T: Plain>(&self) -> io::Result<&Bits<T>> {
+        bits(self)
+    }
+
+    /// Builds [`Pop`] over the whole file. Only the auxiliary data is allocated.
+    ///
+    /// Fails with [`io::ErrorKind::InvalidData`] if the counts overflow [`L1L2`](bits_aux::L1L2),
+    /// build `Pop` in [`WideL1L2`](bits_aux::WideL1L2) over [`Mmap::bits`] for such a file.
+    #[inline]
+    pub fn pop<T: Plain>(&self) -> io::Result<Pop<T, &Bits<T>>> {
+        pop(self.bits()?)
+    }
+}
+
+impl MmapMut {
+    /// Maps the whole `file`, which must be opened for reading and writing.
+    /// Use [`File::set_len`] beforehand to make room for the bits.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated while it is mapped, other than through the map.
+    ///
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_mmap::{Mmap, MmapMut};
+    /// let file = tempfile::tempfile().unwrap();
+    /// file.set_len(1024).unwrap();
+    ///
+    /// let mut mmap = unsafe { MmapMut::map(&file).unwrap() };
+    /// let bits = mmap.bits_mut::<u64>().unwrap();
+    /// bits.set1(3);
+    /// bits.set1(8000);
+    /// mmap.flush().unwrap();
+    /// drop(mmap);
+    ///
+    /// let mmap = unsafe { Mmap::map(&file).unwrap() };
+    /// assert_eq!(mmap[1000], 1);
+    /// assert_eq!(mmap.bits::<u32>().unwrap().select1(1), Some(8000));
+    /// ```
+    pub unsafe fn map(file: &File) -> io::Result<MmapMut> {
+        Map::new(file, libc::PROT_READ | libc::PROT_WRITE).map(|map| MmapMut { map })
+    }
+
+    /// Views the whole file as bits.
+    #[inline]
+    pub fn bits<T: Plain>(&self) -> io::Result<&Bits<T>> {
+        bits(self)
+    }
+
+    /// Views the whole file as mutable bits.
+    #[inline]
+    pub fn bits_mut<T: Plain>(&mut self) -> io::Result<&mut Bits<T>> {
+        bits_mut(self)
+    }
+
+    /// Builds [`Pop`] over the whole file, which keeps the counts in sync on updates.
+    #[inline]
+    pub fn pop_mut<T: Plain>(&mut self) -> io::Result<Pop<T, &mut Bits<T>>> {
+        pop(self.bits_mut()?)
+    }
+
+    /// Writes modified pages back to the file, and waits for the writes to complete.
+    pub fn flush(&self) -> io::Result<()> {
+        if self.map.len == 0 {
+            return Ok(());
+        }
+        if unsafe { libc::msync(self.map.ptr.as_ptr().cast(), self.map.len, libc::MS_SYNC) } == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+}
+
+impl Deref for Mmap {
+    type Target = [u8];
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        self.map.as_slice()
+    }
+}
+
+impl Deref for MmapMut {
+    type Target = [u8];
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        self.map.as_slice()
+    }
+}
+
+impl DerefMut for MmapMut {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [u8] {
+        self.map.as_mut_slice()
+    }
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn pop<T: Plain, R: Deref<Target = Bits<T>>>(repr: R) -> io::Result<Pop<T, R>> {
+    Pop::try_from_repr(repr).map_err(|err| invalid(format!("failed to build Pop: {err}")))
+}
+
+/// Returns the number of words in `bytes` after checking that they can be viewed as `[T]`.
+fn check<T: Plain>(bytes: &[u8]) -> io::Result<usize> {
+    let size = mem::size_of::<T>();
+    if size > 1 && cfg!(target_endian = "big") {
+        return Err(invalid(format!("{size}-byte words are little endian, but the target is big endian")));
+    }
+    if bytes.len() % size != 0 {
+        return Err(invalid(format!("{} bytes are not a multiple of {size}-byte words", bytes.len())));
+    }
+    if !bytes.is_empty() && bytes.as_ptr().align_offset(mem::align_of::<T>()) != 0 {
+        return Err(invalid(format!("bytes are not aligned to {}", mem::align_of::<T>())));
+    }
+    Ok(bytes.len() / size)
+}
+
+/// Views `bytes`, such as a part of a [`Mmap`], as bits.
+///
+/// # Tests
+///
+/// ```
+/// # use bits_mmap::bits;
+/// let words = [1u64 << 8, 1];
+/// let bytes: &[u8] = as_bytes(&words);
+/// assert_eq!(bits::<u64>(bytes).unwrap().select1(1), Some(64));
+/// assert_eq!(bits::<u64>(&bytes[8..]).unwrap().count1(), 1);
+/// assert_eq!(bits::<u16>(&bytes[1..]).unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+/// assert_eq!(bits::<u64>(&bytes[4..12]).unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+/// assert_eq!(bits::<u8>(&bytes[1..2]).unwrap().select1(0), Some(0));
+/// assert_eq!(bits::<u128>(&[]).unwrap().bits(), 0);
+///
+/// fn as_bytes(words: &[u64]) -> &[u8] {
+///     unsafe { std::slice::from_raw_parts(words.as_ptr().cast(), words.len() * 8) }
+/// }
+/// ```
+pub fn bits<T: Plain>(bytes: &[u8]) -> io::Result<&Bits<T>> {
+    let len = check::<T>(bytes)?;
+    let words: &[T] = if len == 0 { &[] } else { unsafe { slice::from_raw_parts(bytes.as_ptr().cast(), len) } };
+    Ok(Bits::new(words))
+}
+
+/// Views `bytes`, such as a part of a [`MmapMut`], as mutable bits.
+pub fn bits_mut<T: Plain>(bytes: &mut [u8]) -> io::Result<&mut Bits<T>> {
+    let len = check::<T>(bytes)?;
+    let words: &mut [T] =
+        if len == 0 { &mut [] } else { unsafe { slice::from_raw_parts_mut(bytes.as_mut_ptr().cast(), len) } };
+    Ok(Bits::new_mut(words))
+}
@@ -0,0 +1,78 @@
+#[macro_use(quickcheck)]
+extern crate quickcheck_macros;
+
+use std::fs::File;
+use std::io::{self, Write};
+
+use bits_aux::Pop;
+use bits_mmap::{Mmap, MmapMut};
+
+fn write_words(words: &[u64]) -> File {
+    let mut file = tempfile::tempfile().unwrap();
+    for w in words {
+        file.write_all(&w.to_le_bytes()).unwrap();
+    }
+    file
+}
+
+#[quickcheck]
+fn pop_over_mmap(words: Vec<u64>) -> bool {
+    let file = write_words(&words);
+    let mmap = unsafe { Mmap::map(&file).unwrap() };
+    let pop = mmap.pop::<u64>().unwrap();
+    let vec = Pop::from(words);
+
+    pop.bits() == vec.bits()
+        && pop.count1() == vec.count1()
+        && (0..pop.bits()).step_by(7).all(|i| pop.rank1(..i) == vec.rank1(..i))
+        && (0..pop.count1()).all(|n| pop.select1(n) == vec.select1(n))
+        && (0..pop.count0()).step_by(5).all(|n| pop.select0(n) == vec.select0(n))
+}
+
+#[quickcheck]
+fn pop_mut_persists(ones: Vec<u16>) -> bool {
+    let file = tempfile::tempfile().unwrap();
+    file.set_len(1 << 13).unwrap();
+    {
+        let mut mmap = unsafe { MmapMut::map(&file).unwrap() };
+        let mut pop = mmap.pop_mut::<u64>().unwrap();
+        for &i in &ones {
+            pop.set1(i as usize);
+        }
+        mmap.flush().unwrap();
+    }
+
+    let mmap = unsafe { Mmap::map(&file).unwrap() };
+    let mut want = Pop::<u64>::new(1 << 16);
+    for &i in &ones {
+        want.set1(i as usize);
+    }
+    mmap.bits::<u64>().unwrap().as_slice() == want.inner().as_slice()
+}
+
+#[test]
+fn empty_file() {
+    let file = tempfile::tempfile().unwrap();
+    let mmap = unsafe { Mmap::map(&file).unwrap() };
+    assert!(mmap.is_empty());
+    assert_eq!(mmap.bits::<u64>().unwrap().bits(), 0);
+    assert_eq!(mmap.pop::<u128>().unwrap().count1(), 0);
+}
+
+#[test]
+fn length_not_multiple_of_words() {
+    let mut file = tempfile::tempfile().unwrap();
+    file.write_all(&[0xFF; 12]).unwrap();
+    let mmap = unsafe { Mmap::map(&file).unwrap() };
+    assert_eq!(mmap.bits::<u32>().unwrap().count1(), 96);
+    assert_eq!(mmap.bits::<u64>().unwrap_err().kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn write_only_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("bits");
+    let file = File::create(&path).unwrap();
+    file.set_len(64).unwrap();
+    assert!(unsafe { MmapMut::map(&file) }.is_err());
+}
@@ -0,0 +1,272 @@
+//! `bits_testkit`
+//!
+//! Conformance checks for implementations of the block traits.
+//! Each check applies the same operations to a block and to a model, the set of enabled bits,
+//! then panics with a message if any answer of the block differs from the model.
+//! The model is sparse and answers are checked at sampled positions,
+//! so that blocks as large as `SmallSet<u32, N>` of `2^32` bits can be checked.
+//!
+//! The checks take [`Op`]s, which implement [`Arbitrary`],
+//! so that they can be used as quickcheck properties directly.
+//!
+//! # Examples
+//!
+//! ```
+//! # use bits_testkit::{check_block, check_pack, Op};
+//! let ops = [Op::Set1(3), Op::Set1(100), Op::Set1(64), Op::Set0(100)];
+//! check_block::<u128>(&ops);
+//! check_block::<[u8; 3]>(&ops);
+//! check_pack::<[u32; 4]>(&ops, &[(10, 12, 0xABC), (60, 64, !0)]);
+//! ```
+//!
+//! ```
+//! # use bits_testkit::Op;
+//! fn my_block(ops: Vec<Op>) {
+//!     bits_testkit::check_block::<[u64; 4]>(&ops);
+//! }
+//! quickcheck::quickcheck(my_block as fn(Vec<Op>));
+//! ```
+
+use std::collections::BTreeSet;
+
+use bits_core::block::*;
+use bits_core::mask::helper::Assign;
+use quickcheck::{Arbitrary, Gen};
+
+/// An update applied to both a block and the model.
+/// The index is taken modulo `BITS` of the block under test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Set1(usize),
+    Set0(usize),
+}
+
+impl Arbitrary for Op {
+    fn arbitrary(g: &mut Gen) -> Self {
+        let i = usize::arbitrary(g);
+        if bool::arbitrary(g) {
+            Op::Set1(i)
+        } else {
+            Op::Set0(i)
+        }
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        match *self {
+            Op::Set1(i) => Box::new(i.shrink().map(Op::Set1)),
+            Op::Set0(i) => Box::new(i.shrink().map(Op::Set0)),
+        }
+    }
+}
+
+/// A block paired with the positions of the bits it is expected to have enabled.
+#[derive(Debug, Clone)]
+pub struct Model<B> {
+    block: B,
+    ones: BTreeSet<usize>,
+}
+
+impl<B: BlockMut> Model<B> {
+    /// Returns an empty block and an empty model.
+    pub fn new() -> Self {
+        Model { block: B::empty(), ones: BTreeSet::new() }
+    }
+
+    /// Returns a model after applying all of `ops`.
+    pub fn from_ops(ops: &[Op]) -> Self {
+        let mut model = Self::new();
+        for &op in ops {
+            model.apply(op);
+        }
+        model
+    }
+
+    pub fn apply(&mut self, op: Op) {
+        match op {
+            Op::Set1(i) => {
+                self.block.set1(i % B::BITS);
+                self.ones.insert(i % B::BITS);
+            }
+            Op::Set0(i) => {
+                self.block.set0(i % B::BITS);
+                self.ones.remove(&(i % B::BITS));
+            }
+        }
+    }
+}
+
+impl<B: BlockMut> Default for Model<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B: Block> Model<B> {
+    #[inline]
+    pub fn block(&self) -> &B {
+        &self.block
+    }
+
+    /// Returns the positions of enabled bits.
+    #[inline]
+    pub fn ones(&self) -> &BTreeSet<usize> {
+        &self.ones
+    }
+
+    #[inline]
+    fn test(&self, i: usize) -> bool {
+        self.ones.contains(&i)
+    }
+
+    fn count1(&self) -> usize {
+        self.ones.len()
+    }
+
+    fn rank1(&self, s: usize, e: usize) -> usize {
+        self.ones.range(s..e).count()
+    }
+
+    /// Returns the position of the n-th zero, or `None` if there are not that many zeros.
+    fn select0(&self, n: usize) -> Option<usize> {
+        // Each one before the candidate pushes it to the right by one.
+        let mut p = n;
+        for &i in &self.ones {
+            if i > p {
+                break;
+            }
+            p += 1;
+        }
+        (p < B::BITS).then_some(p)
+    }
+
+    /// Returns positions at which answers are worth checking:
+    /// both ends, around every one, and evenly spaced positions in between.
+    fn boundaries(&self) -> Vec<usize> {
+        let len = B::BITS;
+        let mut ps = BTreeSet::from([0, len]);
+        ps.extend((0..len).step_by(len.div_ceil(64).max(1)));
+        for &i in &self.ones {
+            ps.extend([i.saturating_sub(1), i, (i + 1).min(len)]);
+        }
+        ps.into_iter().collect()
+    }
+}
+
+/// Checks `test`, `count`, `rank`, `excess` and `select` after applying `ops`.
+pub fn check_block<B: BlockMut + Select>(ops: &[Op]) {
+    let model = Model::<B>::from_ops(ops);
+    check_test(&model);
+    check_rank(&model);
+    check_select(&model);
+}
+
+/// Checks `test` against the model, including out of bounds indices.
+pub fn check_test<B: Block>(model: &Model<B>) {
+    let b = model.block();
+    for i in model.boundaries().into_iter().filter(|&i| i < B::BITS) {
+        assert_eq!(b.test(i), Some(model.test(i)), "test({i})");
+    }
+    assert_eq!(b.test(B::BITS), None, "test(BITS)");
+}
+
+/// Checks `count1`, `count0`, `any`, `all`, `rank1`, `rank0`, `excess1` and `excess0` against the model.
+pub fn check_rank<B: Rank>(model: &Model<B>) {
+    let b = model.block();
+    let ones = model.count1();
+    assert_eq!(b.count1(), ones, "count1");
+    assert_eq!(b.count0(), B::BITS - ones, "count0");
+    assert_eq!(b.any(), ones > 0, "any");
+    assert_eq!(b.all(), ones == B::BITS, "all");
+
+    let ps = model.boundaries();
+    for &p in &ps {
+        let r1 = model.rank1(0, p);
+        assert_eq!(b.rank1(..p), r1, "rank1(..{p})");
+        assert_eq!(b.rank0(..p), p - r1, "rank0(..{p})");
+        assert_eq!(b.rank1(p..), ones - r1, "rank1({p}..)");
+        assert_eq!(b.rank0(p..), B::BITS - p - (ones - r1), "rank0({p}..)");
+    }
+    for w in ps.windows(3) {
+        let (s, e) = (w[0], w[2]);
+        let r1 = model.rank1(s, e);
+        let r0 = e - s - r1;
+        assert_eq!(b.rank1(s..e), r1, "rank1({s}..{e})");
+        assert_eq!(b.rank0(s..e), r0, "rank0({s}..{e})");
+        assert_eq!(b.excess1(s..e), r1.checked_sub(r0), "excess1({s}..{e})");
+        assert_eq!(b.excess0(s..e), r0.checked_sub(r1), "excess0({s}..{e})");
+    }
+}
+
+/// Checks `select1` and `select0` against the model.
+pub fn check_select<B: Select>(model: &Model<B>) {
+    let b = model.block();
+    for (n, &p) in model.ones().iter().enumerate() {
+        assert_eq!(b.select1(n), Some(p), "select1({n})");
+    }
+    assert_eq!(b.select1(model.count1()), None, "select1(count1)");
+
+    // The number of zeros is usually large, check a part of them.
+    let zeros = B::BITS - model.count1();
+    let step = zeros.div_ceil(256).max(1);
+    for n in (0..zeros).step_by(step).chain(zeros.checked_sub(1)) {
+        assert_eq!(b.select0(n), model.select0(n), "select0({n})");
+    }
+    assert_eq!(b.select0(zeros), None, "select0(count0)");
+}
+
+/// Checks `pack` and `unpack` after applying `ops`.
+///
+/// Each of `packs` is `(i, n, bits)`, which writes the lower `n` bits of `bits` at `i`.
+/// `i` and `n` are taken modulo `BITS` and `min(64, BITS - i) + 1` respectively.
+pub fn check_pack<B: Pack>(ops: &[Op], packs: &[(usize, usize, u64)]) {
+    let mut model = Model::<B>::from_ops(ops);
+    for &(i, n, bits) in packs {
+        let i = i % B::BITS;
+        let n = n % ((B::BITS - i).min(64) + 1);
+        let mask = if n == 64 { !0 } else { (1 << n) - 1 };
+
+        let want = (0..n).fold(0u64, |acc, k| acc | (model.test(i + k) as u64) << k);
+        assert_eq!(model.block.unpack::<u64>(i, n), want, "unpack({i}, {n})");
+
+        model.block.pack(i, n, bits);
+        for k in 0..n {
+            if bits >> k & 1 == 1 {
+                model.ones.insert(i + k);
+            } else {
+                model.ones.remove(&(i + k));
+            }
+        }
+        assert_eq!(model.block.unpack::<u64>(i, n), bits & mask, "unpack({i}, {n}) after pack");
+    }
+    check_test(&model);
+}
+
+/// Checks `Assign::{and, not, or, xor}` of two blocks against the model.
+pub fn check_assign<B>(lhs: &[Op], rhs: &[Op])
+where
+    B: BlockMut + Rank + Assign<B>,
+{
+    let a = Model::<B>::from_ops(lhs);
+    let b = Model::<B>::from_ops(rhs);
+
+    // The name, the operation on blocks, and the same operation on bits.
+    type Case<B> = (&'static str, fn(&mut B, &B), fn(bool, bool) -> bool);
+    let ops: [Case<B>; 4] = [
+        ("and", Assign::and, |x, y| x & y),
+        ("not", Assign::not, |x, y| x & !y),
+        ("or", Assign::or, |x, y| x | y),
+        ("xor", Assign::xor, |x, y| x ^ y),
+    ];
+    for (name, assign, f) in ops {
+        let mut c = a.clone();
+        assign(&mut c.block, &b.block);
+        c.ones = a.ones.union(&b.ones).copied().filter(|&i| f(a.test(i), b.test(i))).collect();
+
+        let mut ps = a.boundaries();
+        ps.extend(b.boundaries());
+        for i in ps.into_iter().filter(|&i| i < B::BITS) {
+            assert_eq!(c.block.test(i).unwrap_or_default(), c.test(i), "{name}: test({i})");
+        }
+        assert_eq!(c.block.count1(), c.count1(), "{name}: count1");
+    }
+}
@@ -1,7 +1,12 @@
 //! 1-indexed FenwickTree (BinaryIndexedTree).
 
-use std::iter::Sum;
-use std::ops::{AddAssign, Sub, SubAssign};
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::iter::Sum;
+use core::ops::{AddAssign, Sub, SubAssign};
 
 use bits_core::word::Word;
 pub use index::{children, prefix, search, update};
@@ -0,0 +1,47 @@
+#[macro_use(quickcheck)]
+extern crate quickcheck_macros;
+
+use fmindex::{suffix_array, FmIndex, WaveletMatrix};
+
+/// Maps arbitrary bytes to a small alphabet to make repeats likely.
+fn small(text: Vec<u8>) -> Vec<u8> {
+    text.into_iter().map(|b| b"ab\0\xFF"[b as usize % 4]).collect()
+}
+
+#[quickcheck]
+fn suffix_array_is_sorted(text: Vec<u8>) -> bool {
+    let text = small(text);
+    let mut naive = (0..=text.len()).collect::<Vec<_>>();
+    naive.sort_by_key(|&i| &text[i..]);
+    suffix_array(&text) == naive
+}
+
+#[quickcheck]
+fn wavelet_rank(symbols: Vec<u16>) -> bool {
+    let symbols = symbols.into_iter().map(|c| c as usize % 300).collect::<Vec<_>>();
+    let wm = WaveletMatrix::new(9, &symbols);
+    symbols.iter().enumerate().all(|(i, &c)| wm.get(i) == Some(c))
+        && (0..=symbols.len()).step_by(3).all(|i| {
+            [0, 1, 255, 299]
+                .iter()
+                .chain(symbols.get(i))
+                .all(|&c| wm.rank(c, i) == symbols[..i].iter().filter(|&&x| x == c).count())
+        })
+}
+
+#[quickcheck]
+fn count_and_locate(text: Vec<u8>, pattern: Vec<u8>, step: u8) -> bool {
+    let (text, pattern) = (small(text), small(pattern));
+    let fm = FmIndex::with_sample_rate(&text, step as usize % 8 + 1);
+
+    let naive =
+        |p: &[u8]| (0..=text.len().saturating_sub(p.len())).filter(|&i| text[i..].starts_with(p)).collect::<Vec<_>>();
+    let check = |p: &[u8]| {
+        let mut found = fm.locate(p);
+        found.sort();
+        fm.count(p) == found.len() && found == naive(p)
+    };
+    fm.len() == text.len()
+        && check(&pattern)
+        && (0..text.len()).step_by(5).all(|i| check(&text[i..(i + 3).min(text.len())]))
+}
@@ -0,0 +1,140 @@
+//! `fmindex`
+//!
+//! A self-index of a byte string, built on the bits crates.
+//! [`FmIndex`] counts and locates occurrences of a pattern without the original text,
+//! using the BWT stored in a [`WaveletMatrix`] and a sampled suffix array in an [`IntVec`].
+//!
+//! * [Opportunistic Data Structures with Applications](https://people.unipmn.it/manzini/papers/focs00draft.pdf)
+//!
+//! # Examples
+//!
+//! ```
+//! # use fmindex::FmIndex;
+//! let log = b"GET /a 200\nGET /b 404\nPOST /a 200\n";
+//! let fm = FmIndex::new(log);
+//! assert_eq!(fm.count(b" 200"), 2);
+//! assert_eq!(fm.count(b"PUT"), 0);
+//!
+//! let mut found = fm.locate(b"/a");
+//! found.sort();
+//! assert_eq!(found, [4, 27]);
+//! ```
+
+use std::ops::Range;
+
+use bits::{IntVec, Rank9};
+
+mod sais;
+mod wavelet;
+
+pub use sais::suffix_array;
+pub use wavelet::WaveletMatrix;
+
+/// The number of bits of a symbol, bytes are shifted by one to reserve 0 for the sentinel.
+const WIDTH: usize = 9;
+
+/// An FM-index of a byte string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FmIndex {
+    bwt: WaveletMatrix,
+    // c[x] is the number of symbols smaller than x.
+    c: Vec<usize>,
+    // Rows whose suffix array value is a multiple of `step`.
+    sampled: Rank9,
+    // Suffix array values of sampled rows divided by `step`, in row order.
+    samples: IntVec,
+    step: usize,
+}
+
+impl FmIndex {
+    /// Builds an index which samples every 32nd position of the suffix array.
+    pub fn new(text: &[u8]) -> FmIndex {
+        Self::with_sample_rate(text, 32)
+    }
+
+    /// Builds an index which samples every `step`-th position of the suffix array.
+    /// A larger `step` makes the index smaller and `locate` slower.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `step` is zero.
+    pub fn with_sample_rate(text: &[u8], step: usize) -> FmIndex {
+        assert!(step > 0, "step must be positive");
+
+        let sa = suffix_array(text);
+        let n = sa.len();
+        let symbol = |i: usize| if i == text.len() { 0 } else { text[i] as usize + 1 };
+
+        let bwt = sa.iter().map(|&p| if p == 0 { symbol(n - 1) } else { symbol(p - 1) }).collect::<Vec<_>>();
+        let mut c = vec![0; 1 << WIDTH];
+        for &x in &bwt {
+            c[x] += 1;
+        }
+        let mut sum = 0;
+        for x in c.iter_mut() {
+            sum += std::mem::replace(x, sum);
+        }
+
+        let mut words = vec![0u64; bit::blocks(n, 64)];
+        let width = (usize::BITS - (n / step).leading_zeros()).max(1) as usize;
+        let mut samples = IntVec::new(width);
+        for (i, &p) in sa.iter().enumerate().filter(|(_, &p)| p % step == 0) {
+            words[i / 64] |= 1 << (i % 64);
+            samples.push((p / step) as u64);
+        }
+
+        FmIndex { bwt: WaveletMatrix::new(WIDTH, &bwt), c, sampled: Rank9::from(words), samples, step }
+    }
+
+    /// Returns the length of the indexed text.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.bwt.len() - 1
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Counts occurrences of `pattern`.
+    #[inline]
+    pub fn count(&self, pattern: &[u8]) -> usize {
+        self.rows(pattern).len()
+    }
+
+    /// Returns the starting positions of `pattern`, in no particular order.
+    pub fn locate(&self, pattern: &[u8]) -> Vec<usize> {
+        self.rows(pattern)
+            .map(|mut row| {
+                let mut steps = 0;
+                while self.sampled.test(row) != Some(true) {
+                    row = self.lf(row);
+                    steps += 1;
+                }
+                let k = self.sampled.rank1(..row);
+                self.samples.get(k).expect("sample must exist") as usize * self.step + steps
+            })
+            .collect()
+    }
+
+    /// Returns rows of the suffix array prefixed by `pattern`, by backward search.
+    fn rows(&self, pattern: &[u8]) -> Range<usize> {
+        let mut rows = 0..self.bwt.len();
+        for &b in pattern.iter().rev() {
+            let x = b as usize + 1;
+            rows = self.c[x] + self.bwt.rank(x, rows.start)..self.c[x] + self.bwt.rank(x, rows.end);
+            if rows.is_empty() {
+                return 0..0;
+            }
+        }
+        rows
+    }
+
+    /// Maps a row to the row of the suffix one position before.
+    #[inline]
+    fn lf(&self, row: usize) -> usize {
+        let x = self.bwt.get(row).expect("row out of bounds");
+        self.c[x] + self.bwt.rank(x, row)
+    }
+}
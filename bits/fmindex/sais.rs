@@ -0,0 +1,141 @@
+//! Suffix array construction by induced sorting (SA-IS).
+//!
+//! * [Two Efficient Algorithms for Linear Time Suffix Array Construction](https://ieeexplore.ieee.org/document/5582081)
+
+const EMPTY: usize = usize::MAX;
+
+/// Returns the suffix array of `text` with a virtual sentinel appended,
+/// so the first element is always `text.len()`.
+///
+/// # Tests
+///
+/// ```
+/// # use fmindex::suffix_array;
+/// assert_eq!(suffix_array(b"banana"), [6, 5, 3, 1, 0, 4, 2]);
+/// assert_eq!(suffix_array(b""), [0]);
+/// ```
+pub fn suffix_array(text: &[u8]) -> Vec<usize> {
+    // Shifts each byte by one to reserve 0 for the sentinel.
+    let s = text.iter().map(|&c| c as usize + 1).chain([0]).collect::<Vec<_>>();
+    sais(&s, 257)
+}
+
+/// Builds the suffix array of `s`, whose symbols are less than `k`,
+/// and the last symbol is the unique smallest one.
+fn sais(s: &[usize], k: usize) -> Vec<usize> {
+    let n = s.len();
+    if n == 1 {
+        return vec![0];
+    }
+
+    // true if the suffix at i is S-type, i.e. smaller than the suffix at i + 1.
+    let mut stype = vec![false; n];
+    stype[n - 1] = true;
+    for i in (0..n - 1).rev() {
+        stype[i] = s[i] < s[i + 1] || (s[i] == s[i + 1] && stype[i + 1]);
+    }
+    let is_lms = |i: usize| i > 0 && stype[i] && !stype[i - 1];
+
+    let mut sizes = vec![0; k];
+    for &c in s {
+        sizes[c] += 1;
+    }
+
+    // Sorts LMS substrings.
+    let lms = (1..n).filter(|&i| is_lms(i)).collect::<Vec<_>>();
+    let mut sa = vec![EMPTY; n];
+    induce(s, &stype, &sizes, &lms, &mut sa);
+
+    // Names LMS substrings in sorted order, equal substrings get the same name.
+    let mut names = vec![EMPTY; n];
+    let mut name = 0;
+    let mut prev = None;
+    for p in sa.iter().copied().filter(|&p| is_lms(p)) {
+        if let Some(q) = prev {
+            if !lms_eq(s, &stype, q, p) {
+                name += 1;
+            }
+        }
+        names[p] = name;
+        prev = Some(p);
+    }
+    let reduced = lms.iter().map(|&p| names[p]).collect::<Vec<_>>();
+
+    // Sorts LMS suffixes, by recursion if names are not unique yet.
+    let order = if name + 1 < lms.len() {
+        sais(&reduced, name + 1)
+    } else {
+        let mut order = vec![0; lms.len()];
+        for (i, &r) in reduced.iter().enumerate() {
+            order[r] = i;
+        }
+        order
+    };
+    let sorted = order.into_iter().map(|i| lms[i]).collect::<Vec<_>>();
+
+    induce(s, &stype, &sizes, &sorted, &mut sa);
+    sa
+}
+
+/// Places `lms` at the tails of buckets keeping their order, then induces L-type and S-type suffixes.
+fn induce(s: &[usize], stype: &[bool], sizes: &[usize], lms: &[usize], sa: &mut [usize]) {
+    sa.fill(EMPTY);
+    let mut t = buckets(sizes, true);
+    for &p in lms.iter().rev() {
+        t[s[p]] -= 1;
+        sa[t[s[p]]] = p;
+    }
+
+    let mut h = buckets(sizes, false);
+    for i in 0..sa.len() {
+        if sa[i] != EMPTY && sa[i] > 0 && !stype[sa[i] - 1] {
+            let j = sa[i] - 1;
+            sa[h[s[j]]] = j;
+            h[s[j]] += 1;
+        }
+    }
+
+    let mut t = buckets(sizes, true);
+    for i in (0..sa.len()).rev() {
+        if sa[i] != EMPTY && sa[i] > 0 && stype[sa[i] - 1] {
+            let j = sa[i] - 1;
+            t[s[j]] -= 1;
+            sa[t[s[j]]] = j;
+        }
+    }
+}
+
+/// Returns the start of each bucket, or the end if `tail`.
+fn buckets(sizes: &[usize], tail: bool) -> Vec<usize> {
+    let mut sum = 0;
+    sizes
+        .iter()
+        .map(|&c| {
+            sum += c;
+            if tail {
+                sum
+            } else {
+                sum - c
+            }
+        })
+        .collect()
+}
+
+/// Returns true if LMS substrings starting at `a` and `b` are equal.
+fn lms_eq(s: &[usize], stype: &[bool], a: usize, b: usize) -> bool {
+    let n = s.len();
+    if a == n - 1 || b == n - 1 {
+        return a == b;
+    }
+    let is_lms = |i: usize| i > 0 && stype[i] && !stype[i - 1];
+    for i in 0.. {
+        let (x, y) = (a + i, b + i);
+        if s[x] != s[y] || stype[x] != stype[y] {
+            return false;
+        }
+        if i > 0 && (is_lms(x) || is_lms(y)) {
+            return is_lms(x) && is_lms(y);
+        }
+    }
+    unreachable!()
+}
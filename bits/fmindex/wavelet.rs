@@ -0,0 +1,86 @@
+use bits::Rank9;
+
+/// A wavelet matrix over symbols of `width` bits.
+///
+/// Each level is a [`Rank9`] of one bit of the symbols, from the most significant one,
+/// and symbols are stably partitioned by that bit before the next level.
+///
+/// # Examples
+///
+/// ```
+/// # use fmindex::WaveletMatrix;
+/// let wm = WaveletMatrix::new(3, &[5, 1, 5, 7, 0, 5]);
+/// assert_eq!(wm.len(), 6);
+/// assert_eq!(wm.get(3), Some(7));
+/// assert_eq!(wm.rank(5, 3), 2);
+/// assert_eq!(wm.rank(5, 6), 3);
+/// assert_eq!(wm.rank(2, 6), 0);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WaveletMatrix {
+    levels: Vec<Rank9>,
+    // The number of zeros in each level.
+    zeros: Vec<usize>,
+    len: usize,
+}
+
+impl WaveletMatrix {
+    /// Constructs a wavelet matrix of `symbols`, each of which must fit in `width` bits.
+    pub fn new(width: usize, symbols: &[usize]) -> WaveletMatrix {
+        assert!(width < 64 && symbols.iter().all(|&c| c >> width == 0), "symbols must fit in {width} bits");
+
+        let mut cur = symbols.to_vec();
+        let mut levels = Vec::with_capacity(width);
+        let mut zeros = Vec::with_capacity(width);
+        for l in (0..width).rev() {
+            let mut words = vec![0u64; bit::blocks(cur.len(), 64)];
+            for (i, &c) in cur.iter().enumerate() {
+                words[i / 64] |= ((c >> l & 1) as u64) << (i % 64);
+            }
+            let (lo, hi): (Vec<_>, Vec<_>) = cur.iter().partition(|&&c| c >> l & 1 == 0);
+            zeros.push(lo.len());
+            levels.push(Rank9::from(words));
+            cur = lo.into_iter().chain(hi).collect();
+        }
+        WaveletMatrix { levels, zeros, len: symbols.len() }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the `i`-th symbol.
+    pub fn get(&self, mut i: usize) -> Option<usize> {
+        (i < self.len).then(|| {
+            let mut c = 0;
+            for (level, &zeros) in self.levels.iter().zip(&self.zeros) {
+                let b = level.test(i).expect("index out of bounds");
+                i = if b { zeros + level.rank1(..i) } else { level.rank0(..i) };
+                c = c << 1 | b as usize;
+            }
+            c
+        })
+    }
+
+    /// Counts occurrences of `c` in `..i`.
+    pub fn rank(&self, c: usize, i: usize) -> usize {
+        let width = self.levels.len();
+        let (mut s, mut e) = (0, i.min(self.len));
+        for (l, (level, &zeros)) in self.levels.iter().zip(&self.zeros).enumerate() {
+            if c >> (width - 1 - l) & 1 == 1 {
+                s = zeros + level.rank1(..s);
+                e = zeros + level.rank1(..e);
+            } else {
+                s = level.rank0(..s);
+                e = level.rank0(..e);
+            }
+        }
+        e - s
+    }
+}
@@ -0,0 +1,78 @@
+#![no_main]
+
+//! Builds two masks of `SmallSet` and `Buf` blocks from an operation script,
+//! and checks `and`, `or`, `xor` and `not` of them against `Vec<bool>`.
+//!
+//! Each 3 bytes of the input are a flag and a little endian `u16` index,
+//! where the flag selects the mask to update and whether to set or unset the bit.
+
+use std::borrow::Cow;
+
+use bits::block::*;
+use bits::mask::helper::Assign;
+use bits::mask::Mask;
+use libfuzzer_sys::fuzz_target;
+
+const BLOCKS: usize = 8;
+
+struct Model<B> {
+    blocks: Vec<B>,
+    bits: Vec<bool>,
+}
+
+impl<B: BlockMut> Model<B> {
+    fn new() -> Self {
+        Model { blocks: vec![B::empty(); BLOCKS], bits: vec![false; B::BITS * BLOCKS] }
+    }
+
+    fn apply(&mut self, on: bool, i: usize) {
+        let i = i % self.bits.len();
+        let (q, r) = (i / B::BITS, i % B::BITS);
+        if on {
+            self.blocks[q].set1(r);
+        } else {
+            self.blocks[q].set0(r);
+        }
+        self.bits[i] = on;
+    }
+}
+
+/// Flattens the blocks yielded by a mask, absent blocks are all zeros.
+fn collect<'a, B: Block + 'a>(len: usize, mask: impl IntoIterator<Item = (usize, Cow<'a, B>)>) -> Vec<bool> {
+    let mut bits = vec![false; len];
+    let mut prev = None;
+    for (q, b) in mask {
+        assert!(prev < Some(q), "blocks must be yielded in ascending order");
+        prev = Some(q);
+        for r in 0..B::BITS {
+            bits[q * B::BITS + r] = b.test(r).expect("in bounds");
+        }
+    }
+    bits
+}
+
+fn check<B>(script: &[u8])
+where
+    B: BlockMut + Count + Assign<B>,
+{
+    let mut a = Model::<B>::new();
+    let mut b = Model::<B>::new();
+    for op in script.chunks_exact(3) {
+        let i = u16::from_le_bytes([op[1], op[2]]) as usize;
+        let m = if op[0] & 1 == 0 { &mut a } else { &mut b };
+        m.apply(op[0] & 2 == 0, i);
+    }
+
+    let len = a.bits.len();
+    let naive = |f: fn(bool, bool) -> bool| a.bits.iter().zip(&b.bits).map(|(&x, &y)| f(x, y)).collect::<Vec<_>>();
+    let (x, y) = (a.blocks.as_slice(), b.blocks.as_slice());
+    assert_eq!(collect(len, x.and(y)), naive(|x, y| x & y), "and");
+    assert_eq!(collect(len, x.or(y)), naive(|x, y| x | y), "or");
+    assert_eq!(collect(len, x.xor(y)), naive(|x, y| x ^ y), "xor");
+    assert_eq!(collect(len, x.not(y)), naive(|x, y| x & !y), "not");
+}
+
+fuzz_target!(|data: &[u8]| {
+    check::<SmallSet<u8, 4>>(data);
+    check::<Buf<[u64; 4]>>(data);
+});
@@ -0,0 +1,52 @@
+#![no_main]
+
+//! Applies an operation script to `Pop<u64>`, and checks every answer against `Vec<bool>`.
+//!
+//! The first byte decides the number of bits, and each following 3 bytes are
+//! an opcode and a little endian `u16` operand.
+
+use bits::Pop;
+use libfuzzer_sys::{fuzz_target, Corpus};
+
+fuzz_target!(|data: &[u8]| -> Corpus {
+    let Some((&head, script)) = data.split_first() else {
+        return Corpus::Reject;
+    };
+
+    let mut pop = Pop::<u64>::new(head as usize * 97 + 1);
+    let mut model = vec![false; pop.bits()];
+    let len = model.len();
+
+    for op in script.chunks_exact(3) {
+        let x = u16::from_le_bytes([op[1], op[2]]) as usize;
+        match op[0] % 5 {
+            0 => {
+                pop.set1(x % len);
+                model[x % len] = true;
+            }
+            1 => {
+                pop.set0(x % len);
+                model[x % len] = false;
+            }
+            2 => {
+                let i = x % (len + 1);
+                let want = model[..i].iter().filter(|&&b| b).count();
+                assert_eq!(pop.rank1(..i), want, "rank1(..{i})");
+                assert_eq!(pop.rank0(i..), len - i - (model.iter().filter(|&&b| b).count() - want), "rank0({i}..)");
+            }
+            3 => {
+                let want = model.iter().enumerate().filter(|(_, &b)| b).nth(x).map(|(i, _)| i);
+                assert_eq!(pop.select1(x), want, "select1({x})");
+            }
+            _ => {
+                let want = model.iter().enumerate().filter(|(_, &b)| !b).nth(x).map(|(i, _)| i);
+                assert_eq!(pop.select0(x), want, "select0({x})");
+            }
+        }
+    }
+
+    let ones = model.iter().filter(|&&b| b).count();
+    assert_eq!(pop.count1(), ones);
+    assert_eq!(pop.count0(), len - ones);
+    Corpus::Keep
+});
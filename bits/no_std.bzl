@@ -0,0 +1,38 @@
+def _no_std_transition_impl(_settings, _attr):
+    return {"//bits:std": False}
+
+_no_std_transition = transition(
+    implementation = _no_std_transition_impl,
+    inputs = [],
+    outputs = ["//bits:std"],
+)
+
+def _no_std_build_test_impl(ctx):
+    executable = ctx.actions.declare_file(ctx.label.name)
+    ctx.actions.write(
+        executable,
+        "#!/bin/sh\nexit 0\n",
+        is_executable = True,
+    )
+
+    # Depending on the outputs is enough to fail the test when they can not be built.
+    files = depset(transitive = [t[DefaultInfo].files for t in ctx.attr.targets])
+    return [
+        DefaultInfo(
+            executable = executable,
+            runfiles = ctx.runfiles(transitive_files = files),
+        ),
+    ]
+
+no_std_build_test = rule(
+    doc = "Tests that `targets` can be built with `--//bits:std=false`.",
+    implementation = _no_std_build_test_impl,
+    test = True,
+    attrs = {
+        "targets": attr.label_list(
+            doc = "The targets to build without std.",
+            cfg = _no_std_transition,
+            mandatory = True,
+        ),
+    },
+)
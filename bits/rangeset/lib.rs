@@ -0,0 +1,375 @@
+//! `rangeset`
+
+use std::borrow::Cow;
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+use std::ops::{Bound, Deref, Range, RangeBounds};
+use std::slice;
+
+use bits_aux::Pop;
+use bits_core::block::*;
+use bits_core::mask::Mask;
+use bits_core::word::Word;
+use bits_core::{BitVec, Bits};
+
+/// A set of `usize` stored as sorted half-open ranges.
+///
+/// Ranges are kept normalized, that is, they are non-empty, disjoint and not adjacent to each other,
+/// so that two sets are equal if and only if they contain the same elements.
+///
+/// # Examples
+///
+/// ```
+/// # use rangeset::RangeSet;
+/// let mut set = RangeSet::new();
+/// set.insert_range(10..20);
+/// set.insert_range(30..40);
+/// set.insert_range(20..25);
+/// assert_eq!(set.ranges(), [10..25, 30..40]);
+///
+/// set.remove_range(12..32);
+/// assert_eq!(set.ranges(), [10..12, 32..40]);
+/// assert!(set.contains(11) && !set.contains(12));
+/// assert_eq!(set.count1(), 10);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RangeSet {
+    ranges: Vec<Range<usize>>,
+    // cum[k] is the number of elements in ranges[..k].
+    cum: Vec<usize>,
+}
+
+impl RangeSet {
+    /// Constructs a new, empty `RangeSet`.
+    #[inline]
+    pub fn new() -> RangeSet {
+        RangeSet { ranges: Vec::new(), cum: vec![0] }
+    }
+
+    /// Returns the normalized ranges in ascending order.
+    #[inline]
+    pub fn ranges(&self) -> &[Range<usize>] {
+        &self.ranges
+    }
+
+    #[inline]
+    pub fn iter(&self) -> slice::Iter<'_, Range<usize>> {
+        self.ranges.iter()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Returns the end of the last range, or 0 if empty.
+    #[inline]
+    pub fn end(&self) -> usize {
+        self.ranges.last().map_or(0, |r| r.end)
+    }
+
+    /// Returns the number of elements.
+    #[inline]
+    pub fn count1(&self) -> usize {
+        self.cum[self.ranges.len()]
+    }
+
+    /// Returns true if `i` is in the set.
+    #[inline]
+    pub fn contains(&self, i: usize) -> bool {
+        let k = self.ranges.partition_point(|r| r.end <= i);
+        k < self.ranges.len() && self.ranges[k].start <= i
+    }
+
+    /// Adds all elements in `r`, merging ranges which overlap or touch `r`.
+    pub fn insert_range(&mut self, r: Range<usize>) {
+        if r.is_empty() {
+            return;
+        }
+        let lo = self.ranges.partition_point(|x| x.end < r.start);
+        let hi = self.ranges.partition_point(|x| x.start <= r.end);
+        let merged = if lo < hi { self.ranges[lo].start.min(r.start)..self.ranges[hi - 1].end.max(r.end) } else { r };
+        self.ranges.splice(lo..hi, [merged]);
+        self.fix(lo);
+    }
+
+    /// Removes all elements in `r`, splitting a range which contains `r`.
+    pub fn remove_range(&mut self, r: Range<usize>) {
+        if r.is_empty() {
+            return;
+        }
+        let lo = self.ranges.partition_point(|x| x.end <= r.start);
+        let hi = self.ranges.partition_point(|x| x.start < r.end);
+        if lo == hi {
+            return;
+        }
+        let head = self.ranges[lo].start..r.start;
+        let tail = r.end..self.ranges[hi - 1].end;
+        self.ranges.splice(lo..hi, [head, tail].into_iter().filter(|x| !x.is_empty()));
+        self.fix(lo);
+    }
+
+    /// Recomputes `cum` after `ranges[k..]` changed.
+    fn fix(&mut self, k: usize) {
+        self.cum.truncate(k + 1);
+        for r in &self.ranges[k..] {
+            let c = self.cum[self.cum.len() - 1];
+            self.cum.push(c + r.len());
+        }
+    }
+
+    /// Appends `r` which must not start before the end of the last range.
+    fn push(&mut self, r: Range<usize>) {
+        debug_assert!(self.end() <= r.start);
+        match self.ranges.last_mut() {
+            Some(last) if last.end == r.start => last.end = r.end,
+            _ => {
+                self.ranges.push(r);
+                self.cum.push(0);
+            }
+        }
+        let k = self.ranges.len();
+        self.cum[k] = self.cum[k - 1] + self.ranges[k - 1].len();
+    }
+
+    #[inline]
+    fn bounded<R: RangeBounds<usize>>(&self, r: &R) -> Range<usize> {
+        let Range { start, end } = bit::bounded(r, 0, usize::MAX);
+        match r.end_bound() {
+            Bound::Unbounded => start..self.end().max(start),
+            _ => start..end,
+        }
+    }
+
+    /// Counts elements less than `p`.
+    #[inline]
+    fn rank1_to(&self, p: usize) -> usize {
+        let k = self.ranges.partition_point(|r| r.end <= p);
+        self.cum[k] + self.ranges.get(k).map_or(0, |r| p.saturating_sub(r.start))
+    }
+
+    /// Counts elements in `r`. An unbounded end is `self.end()`.
+    ///
+    /// # Tests
+    ///
+    /// ```
+    /// # use rangeset::RangeSet;
+    /// let set = RangeSet::from_iter([3..6, 10..12]);
+    /// assert_eq!(set.rank1(..), 5);
+    /// assert_eq!(set.rank1(..4), 1);
+    /// assert_eq!(set.rank1(5..11), 2);
+    /// assert_eq!(set.rank0(..11), 7);
+    /// ```
+    #[inline]
+    pub fn rank1<R: RangeBounds<usize>>(&self, r: R) -> usize {
+        let Range { start, end } = self.bounded(&r);
+        self.rank1_to(end) - self.rank1_to(start)
+    }
+
+    /// Counts non-elements in `r`. An unbounded end is `self.end()`.
+    #[inline]
+    pub fn rank0<R: RangeBounds<usize>>(&self, r: R) -> usize {
+        let r = self.bounded(&r);
+        r.len() - self.rank1(r)
+    }
+
+    /// Returns the `n`-th smallest element, or `None` if `n >= self.count1()`.
+    ///
+    /// # Tests
+    ///
+    /// ```
+    /// # use rangeset::RangeSet;
+    /// let set = RangeSet::from_iter([3..6, 10..12]);
+    /// assert_eq!(set.select1(0), Some(3));
+    /// assert_eq!(set.select1(3), Some(10));
+    /// assert_eq!(set.select1(5), None);
+    /// assert_eq!(set.select0(3), Some(6));
+    /// assert_eq!(set.select0(7), Some(12));
+    /// ```
+    pub fn select1(&self, n: usize) -> Option<usize> {
+        (n < self.count1()).then(|| {
+            let k = self.cum.partition_point(|&c| c <= n) - 1;
+            self.ranges[k].start + (n - self.cum[k])
+        })
+    }
+
+    /// Returns the `n`-th smallest non-element.
+    /// This never fails because the set is finite.
+    pub fn select0(&self, n: usize) -> Option<usize> {
+        // Finds the number of ranges which have at most `n` non-elements before them.
+        let (mut lo, mut hi) = (0, self.ranges.len());
+        while lo < hi {
+            let k = lo + (hi - lo) / 2;
+            if self.ranges[k].start - self.cum[k] <= n {
+                lo = k + 1;
+            } else {
+                hi = k;
+            }
+        }
+        Some(n + self.cum[lo])
+    }
+
+    /// Returns a [`Mask`] which yields blocks of `T` overlapping the ranges.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rangeset::RangeSet;
+    /// # use bits_core::mask::Mask;
+    /// # use bits_core::BitVec;
+    /// let set = RangeSet::from_iter([3..6, 62..66]);
+    /// let mut bv = BitVec::<u64>::new(128);
+    /// bv.set1(4);
+    /// bv.set1(65);
+    /// bv.set1(100);
+    ///
+    /// let and = RangeSet::from_blocks(set.blocks::<u64>().and(bv.as_slice()));
+    /// assert_eq!(and.ranges(), [4..5, 65..66]);
+    /// let or = RangeSet::from_blocks(set.blocks::<u64>().or(bv.as_slice()));
+    /// assert_eq!(or.ranges(), [3..6, 62..66, 100..101]);
+    /// ```
+    #[inline]
+    pub fn blocks<T: Word>(&self) -> Blocks<'_, T> {
+        Blocks { ranges: self.ranges.iter(), rest: None, _block: PhantomData }
+    }
+
+    /// Collects set bits of blocks yielded by a [`Mask`], e.g. the result of `and` or `or`.
+    pub fn from_blocks<T, B, M>(mask: M) -> RangeSet
+    where
+        T: Word,
+        B: Deref<Target = T>,
+        M: Mask<Bits = B>,
+    {
+        let mut set = RangeSet::new();
+        for (q, b) in mask.into_mask() {
+            set.extend_block(q * T::BITS, &*b);
+        }
+        set
+    }
+
+    /// Appends runs of ones in a block which starts at `offset`.
+    fn extend_block<T: Block + Count>(&mut self, offset: usize, b: &T) {
+        if b.all() {
+            self.push(offset..offset + T::BITS);
+        } else if b.any() {
+            for i in (0..T::BITS).filter(|&i| b.test(i) == Some(true)) {
+                self.push(offset + i..offset + i + 1);
+            }
+        }
+    }
+}
+
+impl Default for RangeSet {
+    #[inline]
+    fn default() -> Self {
+        RangeSet::new()
+    }
+}
+
+impl FromIterator<Range<usize>> for RangeSet {
+    fn from_iter<I: IntoIterator<Item = Range<usize>>>(iter: I) -> Self {
+        let mut set = RangeSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<Range<usize>> for RangeSet {
+    fn extend<I: IntoIterator<Item = Range<usize>>>(&mut self, iter: I) {
+        for r in iter {
+            self.insert_range(r);
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a RangeSet {
+    type Item = &'a Range<usize>;
+    type IntoIter = slice::Iter<'a, Range<usize>>;
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: Block + Count> From<&Bits<T>> for RangeSet {
+    fn from(bits: &Bits<T>) -> Self {
+        let mut set = RangeSet::new();
+        for (q, b) in bits.as_slice().iter().enumerate() {
+            set.extend_block(q * T::BITS, b);
+        }
+        set
+    }
+}
+
+impl<T: Block + Count> From<&Pop<T>> for RangeSet {
+    #[inline]
+    fn from(pop: &Pop<T>) -> Self {
+        RangeSet::from(pop.inner())
+    }
+}
+
+impl<T: Word> From<&RangeSet> for BitVec<T> {
+    /// Converts into bits which have enough blocks to hold `set.end()`.
+    fn from(set: &RangeSet) -> Self {
+        BitVec::from(to_blocks(set))
+    }
+}
+
+impl<T: Word> From<&RangeSet> for Pop<T> {
+    fn from(set: &RangeSet) -> Self {
+        Pop::from(to_blocks::<T>(set))
+    }
+}
+
+fn to_blocks<T: Word>(set: &RangeSet) -> Vec<T> {
+    let mut data = vec![T::empty(); bit::blocks(set.end(), T::BITS)];
+    let bits = Bits::new_mut(&mut data);
+    for r in set {
+        bits.set1_range(r.clone());
+    }
+    data
+}
+
+/// A [`Mask`] over a [`RangeSet`], see [`RangeSet::blocks`].
+#[derive(Debug, Clone)]
+pub struct Blocks<'a, T> {
+    ranges: slice::Iter<'a, Range<usize>>,
+    // A part of a range which is not yielded yet.
+    rest: Option<Range<usize>>,
+    _block: PhantomData<T>,
+}
+
+impl<'a, T: Word + 'a> Iterator for Blocks<'a, T> {
+    type Item = (usize, Cow<'a, T>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut r = self.rest.take().or_else(|| self.ranges.next().cloned())?;
+        let q = r.start / T::BITS;
+        let (head, tail) = (q * T::BITS, (q + 1) * T::BITS);
+        let mut block = T::empty();
+        loop {
+            Bits::new_mut(std::slice::from_mut(&mut block)).set1_range(r.start - head..r.end.min(tail) - head);
+            if r.end > tail {
+                self.rest = Some(tail..r.end);
+                break;
+            }
+            match self.ranges.next() {
+                Some(next) if next.start < tail => r = next.clone(),
+                next => {
+                    self.rest = next.cloned();
+                    break;
+                }
+            }
+        }
+        Some((q, Cow::Owned(block)))
+    }
+}
+
+impl<'a, T: Word + 'a> Mask for Blocks<'a, T> {
+    type Bits = Cow<'a, T>;
+    type Iter = Self;
+    #[inline]
+    fn into_mask(self) -> Self::Iter {
+        self
+    }
+}
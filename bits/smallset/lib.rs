@@ -1,14 +1,37 @@
-use std::cmp::Ordering::{self, Equal as EQ, Greater as GT, Less as LT};
-use std::iter::Peekable;
+use std::cmp::Ordering::{Equal as EQ, Greater as GT, Less as LT};
 use std::ops::{Deref, DerefMut, Range, RangeBounds};
 
 use bits_core::block::*;
 use bits_core::mask::helper;
+use bits_core::SpaceUsage;
 use smallvec::SmallVec;
 
+/// A sorted set of `T` that behaves as a bits block of `2^T::BITS` bits.
+///
+/// `SmallSet<u8, N>`, `SmallSet<u16, N>` and `SmallSet<u32, N>` are blocks
+/// of 256, 65536 and 2^32 bits respectively.
+/// `SmallSet<u32, N>` is available only on 64-bit targets, where 2^32 fits in `usize`.
 #[derive(Debug, Default, Clone)]
 pub struct SmallSet<T, const N: usize>(SmallVec<T, N>);
 
+mod private {
+    pub trait Key: Copy + Ord + num::PrimInt {
+        /// The number of bits of a block keyed by `Self`.
+        const BITS: usize;
+    }
+    impl Key for u8 {
+        const BITS: usize = u8::MAX as usize + 1;
+    }
+    impl Key for u16 {
+        const BITS: usize = u16::MAX as usize + 1;
+    }
+    #[cfg(target_pointer_width = "64")]
+    impl Key for u32 {
+        const BITS: usize = u32::MAX as usize + 1;
+    }
+}
+use private::Key;
+
 impl<T, const N: usize> AsRef<[T]> for SmallSet<T, N> {
     #[inline]
     fn as_ref(&self) -> &[T] {
@@ -35,21 +58,115 @@ impl<T, const N: usize> SmallSet<T, N> {
     }
 }
 
-impl<const N: usize> SmallSet<u16, N> {
+impl<T: Key, const N: usize> SmallSet<T, N> {
     const fn bits(&self) -> usize {
         Self::BITS
     }
+
+    /// Builds a set from indices sorted in ascending order.
+    /// Duplicated indices are allowed, and stored only once.
+    ///
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_core::block::*;
+    /// # use smallset::SmallSet;
+    /// let b = SmallSet::<u16, 4>::from_sorted_iter([1, 3, 3, 5, 65535]);
+    /// assert_eq!(b.as_ref(), &[1, 3, 5, 65535]);
+    /// assert_eq!(b.count1(), 4);
+    /// ```
+    pub fn from_sorted_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut vec = SmallVec::<T, N>::new();
+        for i in iter {
+            assert!(i < Self::BITS);
+            let i = num::cast(i).unwrap();
+            match vec.last() {
+                Some(&last) if last == i => {}
+                Some(&last) => {
+                    assert!(last < i, "indices must be sorted");
+                    vec.push(i);
+                }
+                None => vec.push(i),
+            }
+        }
+        SmallSet(vec)
+    }
+
+    /// Builds a set from indices in arbitrary order.
+    ///
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_core::block::*;
+    /// # use smallset::SmallSet;
+    /// let b = SmallSet::<u8, 4>::from_unsorted([200, 3, 100, 3, 0]);
+    /// assert_eq!(b.as_ref(), &[0, 3, 100, 200]);
+    /// ```
+    pub fn from_unsorted<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut vec = iter
+            .into_iter()
+            .map(|i| {
+                assert!(i < Self::BITS);
+                num::cast(i).unwrap()
+            })
+            .collect::<SmallVec<T, N>>();
+        vec.sort_unstable();
+        vec.dedup();
+        SmallSet(vec)
+    }
+
+    /// Enables all bits at the given indices, which must be sorted in ascending order.
+    ///
+    /// Unlike calling `set1` for each index, merges the batch in a single pass.
+    ///
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_core::block::*;
+    /// # use smallset::SmallSet;
+    /// let mut b = SmallSet::<u16, 4>::from_sorted_iter([2, 4, 6]);
+    /// b.extend_sorted([1, 2, 3, 7]);
+    /// assert_eq!(b.as_ref(), &[1, 2, 3, 4, 6, 7]);
+    /// ```
+    pub fn extend_sorted<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        let batch = Self::from_sorted_iter(iter);
+        self.0 = union(self.as_slice(), batch.as_slice());
+    }
+
+    /// Disables all bits in the given range.
+    ///
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_core::block::*;
+    /// # use smallset::SmallSet;
+    /// let mut b = SmallSet::<u16, 4>::from_sorted_iter([1, 2, 3, 4, 5, 100]);
+    /// b.remove_range(2..5);
+    /// assert_eq!(b.as_ref(), &[1, 5, 100]);
+    /// b.remove_range(50..);
+    /// assert_eq!(b.as_ref(), &[1, 5]);
+    /// b.remove_range(..);
+    /// assert_eq!(b.count1(), 0);
+    /// ```
+    pub fn remove_range<R: RangeBounds<usize>>(&mut self, r: R) {
+        let Range { start, end } = bit::bounded(&r, 0, self.bits());
+        let p = self.rank1(..start);
+        let q = p + self.rank1(start..end);
+        self.0.drain(p..q);
+    }
 }
 
-impl<const N: usize> Block for SmallSet<u16, N> {
-    const BITS: usize = u16::MAX as usize + 1;
+impl<T: Key, const N: usize> Block for SmallSet<T, N> {
+    const BITS: usize = <T as Key>::BITS;
 
     /// # Tests
     ///
     /// ```
     /// # use bits_core::block::*;
     /// # use smallset::SmallSet;
+    /// assert_eq!(SmallSet::<u8, 4>::BITS, 256);
     /// assert_eq!(SmallSet::<u16, 4>::BITS, 65536);
+    /// assert_eq!(SmallSet::<u32, 4>::BITS, 1 << 32);
     /// ```
     #[inline]
     fn empty() -> Self {
@@ -58,11 +175,11 @@ impl<const N: usize> Block for SmallSet<u16, N> {
 
     #[inline]
     fn test(&self, i: usize) -> Option<bool> {
-        num::cast(i).and_then(|k| self.as_slice().binary_search(&k).map(|_found| true).ok())
+        num::cast(i).map(|k: T| self.as_slice().binary_search(&k).is_ok())
     }
 }
 
-impl<const N: usize> BlockMut for SmallSet<u16, N> {
+impl<T: Key, const N: usize> BlockMut for SmallSet<T, N> {
     /// # Tests
     ///
     /// ```
@@ -72,6 +189,10 @@ impl<const N: usize> BlockMut for SmallSet<u16, N> {
     /// b.set1(100);
     /// assert_eq!(b.test(100), Some(true));
     /// assert_eq!(b.count1(), 1);
+    ///
+    /// let mut b = smallset::SmallSet::<u32, 4>::empty();
+    /// b.set1(1 << 31);
+    /// assert_eq!(b.test(1 << 31), Some(true));
     /// ```
     #[inline]
     fn set1(&mut self, i: usize) {
@@ -91,7 +212,8 @@ impl<const N: usize> BlockMut for SmallSet<u16, N> {
     /// b.set1(100);
     /// assert_eq!(b.test(100), Some(true));
     /// b.set0(100);
-    /// assert_eq!(b.test(100), None);
+    /// assert_eq!(b.test(100), Some(false));
+    /// assert_eq!(b.test(1 << 16), None);
     /// ```
     #[inline]
     fn set0(&mut self, i: usize) {
@@ -103,7 +225,7 @@ impl<const N: usize> BlockMut for SmallSet<u16, N> {
     }
 }
 
-impl<const N: usize> Count for SmallSet<u16, N> {
+impl<T: Key, const N: usize> Count for SmallSet<T, N> {
     /// # Tests
     ///
     /// ```
@@ -121,7 +243,7 @@ impl<const N: usize> Count for SmallSet<u16, N> {
     }
 }
 
-impl<const N: usize> Rank for SmallSet<u16, N> {
+impl<T: Key, const N: usize> Rank for SmallSet<T, N> {
     /// # Tests
     ///
     /// ```
@@ -134,10 +256,17 @@ impl<const N: usize> Rank for SmallSet<u16, N> {
     /// assert_eq!(b.rank1(..), 3);
     /// assert_eq!(b.rank1(..65530), 2);
     /// assert_eq!(b.rank1(..65536), 3);
+    ///
+    /// let mut b = smallset::SmallSet::<u8, 12>::empty();
+    /// b.set1(255);
+    /// b.set1(10);
+    /// assert_eq!(b.rank1(..255), 1);
+    /// assert_eq!(b.rank1(..256), 2);
+    /// assert_eq!(b.rank0(..256), 254);
     /// ```
     fn rank1<R: RangeBounds<usize>>(&self, r: R) -> usize {
         let rank = |i| {
-            let i = num::cast(i).unwrap();
+            let i: T = num::cast(i).unwrap();
             // Search the smallest index `p` that satisfy `vec[p] >= i`,
             // `p` also implies the number of enabled bits in [0, p).
             // For example, searching 5 in `[0, 1, 7]` return 2.
@@ -158,7 +287,7 @@ impl<const N: usize> Rank for SmallSet<u16, N> {
     }
 }
 
-impl<const N: usize> Select for SmallSet<u16, N> {
+impl<T: Key, const N: usize> Select for SmallSet<T, N> {
     /// # Tests
     ///
     /// ```
@@ -174,19 +303,80 @@ impl<const N: usize> Select for SmallSet<u16, N> {
     /// assert_eq!(b.select1(3), None);
     /// ```
     fn select1(&self, n: usize) -> Option<usize> {
-        self.as_slice().get(n).map(|&u| u as usize)
+        self.as_slice().get(n).map(|&u| num::cast(u).unwrap())
     }
 }
 
-fn cmp_opt<T: Ord>(a: Option<&T>, b: Option<&T>, a_is_none: Ordering, b_is_none: Ordering) -> Ordering {
-    match (a, b) {
-        (None, _) => a_is_none,
-        (_, None) => b_is_none,
-        (Some(x), Some(y)) => x.cmp(y),
+/// Returns the smallest index `k` in `[i, xs.len())` that satisfies `xs[k] >= x`,
+/// or `xs.len()` if there is no such index.
+///
+/// Probes `xs[i+1], xs[i+2], xs[i+4], ...` before a binary search,
+/// so skipping `d` elements costs `O(log d)` instead of `O(d)`.
+/// This matters when one set is much larger than the other.
+fn gallop<T: Ord>(xs: &[T], mut i: usize, x: &T) -> usize {
+    let mut step = 1;
+    let mut j = i + 1;
+    while j < xs.len() && xs[j] < *x {
+        i = j;
+        step <<= 1;
+        j = i + step;
+    }
+    let j = j.min(xs.len());
+    i + xs[i..j].partition_point(|v| v < x)
+}
+
+/// Merges two sorted slices into a sorted vector without duplicates.
+fn union<T: Ord + Copy, const N: usize>(xs: &[T], ys: &[T]) -> SmallVec<T, N> {
+    let mut out = SmallVec::with_capacity(xs.len() + ys.len());
+    let (mut i, mut j) = (0, 0);
+    while i < xs.len() && j < ys.len() {
+        match xs[i].cmp(&ys[j]) {
+            LT => {
+                let k = gallop(xs, i, &ys[j]);
+                out.extend_from_slice(&xs[i..k]);
+                i = k;
+            }
+            EQ => {
+                out.push(xs[i]);
+                i += 1;
+                j += 1;
+            }
+            GT => {
+                let k = gallop(ys, j, &xs[i]);
+                out.extend_from_slice(&ys[j..k]);
+                j = k;
+            }
+        }
     }
+    out.extend_from_slice(&xs[i..]);
+    out.extend_from_slice(&ys[j..]);
+    out
 }
 
-impl<const N: usize, const M: usize> helper::Assign<SmallSet<u16, M>> for SmallSet<u16, N> {
+impl<T: Key, const N: usize> SpaceUsage for SmallSet<T, N> {
+    /// # Tests
+    ///
+    /// ```
+    /// # use bits_core::block::*;
+    /// # use bits_core::SpaceUsage;
+    /// # use smallset::SmallSet;
+    /// let mut s = SmallSet::<u16, 4>::empty();
+    /// s.set1(1);
+    /// assert_eq!(s.heap_bytes(), 0);
+    /// (0..10).for_each(|i| s.set1(i));
+    /// assert!(s.heap_bytes() >= 10 * 2);
+    /// ```
+    #[inline]
+    fn heap_bytes(&self) -> usize {
+        if self.0.spilled() {
+            self.0.capacity() * std::mem::size_of::<T>()
+        } else {
+            0
+        }
+    }
+}
+
+impl<T: Key, const N: usize, const M: usize> helper::Assign<SmallSet<T, M>> for SmallSet<T, N> {
     /// # Tests
     ///
     /// ```
@@ -205,39 +395,22 @@ impl<const N: usize, const M: usize> helper::Assign<SmallSet<u16, M>> for SmallS
     /// Assign::and(&mut a, &b);
     /// assert_eq!(a.as_ref(), &[2, 3]);
     /// ```
-    fn and(a: &mut Self, b: &SmallSet<u16, M>) {
-        a.0 = Iter { a: a.as_slice().iter().peekable(), b: b.as_slice().iter().peekable() }.collect();
-
-        struct Iter<A: Iterator, B: Iterator> {
-            a: Peekable<A>,
-            b: Peekable<B>,
-        }
-        impl<'a, 'b, A, B> Iterator for Iter<A, B>
-        where
-            A: Iterator<Item = &'a u16>,
-            B: Iterator<Item = &'b u16>,
-        {
-            type Item = u16;
-
-            fn next(&mut self) -> Option<Self::Item> {
-                loop {
-                    match self.a.peek()?.cmp(self.b.peek()?) {
-                        LT => {
-                            self.a.next();
-                        }
-                        EQ => {
-                            let a = self.a.next().unwrap();
-                            let b = self.b.next().unwrap();
-                            debug_assert_eq!(a, b);
-                            break Some(*a);
-                        }
-                        GT => {
-                            self.b.next();
-                        }
-                    }
+    fn and(a: &mut Self, b: &SmallSet<T, M>) {
+        let (xs, ys) = (a.as_slice(), b.as_slice());
+        let mut out = SmallVec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < xs.len() && j < ys.len() {
+            match xs[i].cmp(&ys[j]) {
+                LT => i = gallop(xs, i, &ys[j]),
+                EQ => {
+                    out.push(xs[i]);
+                    i += 1;
+                    j += 1;
                 }
+                GT => j = gallop(ys, j, &xs[i]),
             }
         }
+        a.0 = out;
     }
 
     /// # Tests
@@ -258,36 +431,26 @@ impl<const N: usize, const M: usize> helper::Assign<SmallSet<u16, M>> for SmallS
     /// Assign::not(&mut a, &b);
     /// assert_eq!(a.as_ref(), &[1]);
     /// ```
-    fn not(a: &mut Self, b: &SmallSet<u16, M>) {
-        a.0 = Iter { a: a.as_slice().iter().peekable(), b: b.as_slice().iter().peekable() }.collect();
-
-        struct Iter<A: Iterator, B: Iterator> {
-            a: Peekable<A>,
-            b: Peekable<B>,
-        }
-        impl<'a, 'b, A, B> Iterator for Iter<A, B>
-        where
-            A: Iterator<Item = &'a u16>,
-            B: Iterator<Item = &'b u16>,
-        {
-            type Item = u16;
-
-            fn next(&mut self) -> Option<Self::Item> {
-                loop {
-                    match cmp_opt(self.a.peek(), self.b.peek(), LT, LT) {
-                        LT => break self.a.next().copied(),
-                        EQ => {
-                            let a = self.a.next().unwrap();
-                            let b = self.b.next().unwrap();
-                            debug_assert_eq!(a, b);
-                        }
-                        GT => {
-                            self.b.next().unwrap();
-                        }
-                    }
+    fn not(a: &mut Self, b: &SmallSet<T, M>) {
+        let (xs, ys) = (a.as_slice(), b.as_slice());
+        let mut out = SmallVec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < xs.len() && j < ys.len() {
+            match xs[i].cmp(&ys[j]) {
+                LT => {
+                    let k = gallop(xs, i, &ys[j]);
+                    out.extend_from_slice(&xs[i..k]);
+                    i = k;
                 }
+                EQ => {
+                    i += 1;
+                    j += 1;
+                }
+                GT => j = gallop(ys, j, &xs[i]),
             }
         }
+        out.extend_from_slice(&xs[i..]);
+        a.0 = out;
     }
 
     /// # Tests
@@ -308,33 +471,8 @@ impl<const N: usize, const M: usize> helper::Assign<SmallSet<u16, M>> for SmallS
     /// Assign::or(&mut a, &b);
     /// assert_eq!(a.as_ref(), &[1, 2, 3, 4]);
     /// ```
-    fn or(a: &mut Self, b: &SmallSet<u16, M>) {
-        a.0 = Iter { a: a.as_slice().iter().peekable(), b: b.as_slice().iter().peekable() }.collect();
-
-        struct Iter<A: Iterator, B: Iterator> {
-            a: Peekable<A>,
-            b: Peekable<B>,
-        }
-        impl<'a, 'b, A, B> Iterator for Iter<A, B>
-        where
-            A: Iterator<Item = &'a u16>,
-            B: Iterator<Item = &'b u16>,
-        {
-            type Item = u16;
-
-            fn next(&mut self) -> Option<Self::Item> {
-                match cmp_opt(self.a.peek(), self.b.peek(), GT, LT) {
-                    LT => self.a.next().copied(),
-                    EQ => {
-                        let a = self.a.next().unwrap();
-                        let b = self.b.next().unwrap();
-                        debug_assert_eq!(a, b);
-                        Some(*a)
-                    }
-                    GT => self.b.next().copied(),
-                }
-            }
-        }
+    fn or(a: &mut Self, b: &SmallSet<T, M>) {
+        a.0 = union(a.as_slice(), b.as_slice());
     }
 
     /// # Tests
@@ -355,33 +493,30 @@ impl<const N: usize, const M: usize> helper::Assign<SmallSet<u16, M>> for SmallS
     /// Assign::xor(&mut a, &b);
     /// assert_eq!(a.as_ref(), &[1, 4]);
     /// ```
-    fn xor(a: &mut Self, b: &SmallSet<u16, M>) {
-        a.0 = Iter { a: a.as_slice().iter().peekable(), b: b.as_slice().iter().peekable() }.collect();
-
-        struct Iter<L: Iterator, R: Iterator> {
-            a: Peekable<L>,
-            b: Peekable<R>,
-        }
-        impl<'a, 'b, A, B> Iterator for Iter<A, B>
-        where
-            A: Iterator<Item = &'a u16>,
-            B: Iterator<Item = &'b u16>,
-        {
-            type Item = u16;
-
-            fn next(&mut self) -> Option<Self::Item> {
-                loop {
-                    match cmp_opt(self.a.peek(), self.b.peek(), GT, LT) {
-                        LT => break self.a.next().copied(),
-                        EQ => {
-                            let a = self.a.next().unwrap();
-                            let b = self.b.next().unwrap();
-                            debug_assert_eq!(a, b);
-                        }
-                        GT => break self.b.next().copied(),
-                    }
+    fn xor(a: &mut Self, b: &SmallSet<T, M>) {
+        let (xs, ys) = (a.as_slice(), b.as_slice());
+        let mut out = SmallVec::with_capacity(xs.len() + ys.len());
+        let (mut i, mut j) = (0, 0);
+        while i < xs.len() && j < ys.len() {
+            match xs[i].cmp(&ys[j]) {
+                LT => {
+                    let k = gallop(xs, i, &ys[j]);
+                    out.extend_from_slice(&xs[i..k]);
+                    i = k;
+                }
+                EQ => {
+                    i += 1;
+                    j += 1;
+                }
+                GT => {
+                    let k = gallop(ys, j, &xs[i]);
+                    out.extend_from_slice(&ys[j..k]);
+                    j = k;
                 }
             }
         }
+        out.extend_from_slice(&xs[i..]);
+        out.extend_from_slice(&ys[j..]);
+        a.0 = out;
     }
 }
@@ -0,0 +1,31 @@
+#[macro_use(quickcheck)]
+extern crate quickcheck_macros;
+
+use std::collections::BTreeSet;
+
+use bits::block::*;
+
+#[quickcheck]
+fn adaptive_matches_model(ops: Vec<(bool, u16)>) -> bool {
+    let mut block = Adaptive::<8>::empty();
+    let mut model = BTreeSet::new();
+
+    // Fill a dense range first so that removals cross the demotion threshold.
+    for i in 0..5000 {
+        block.set1(i);
+        model.insert(i);
+    }
+    for (i, &(bit, x)) in ops.iter().enumerate() {
+        let x = if i % 2 == 0 { x as usize % 5000 } else { x as usize };
+        if bit {
+            block.set1(x);
+            model.insert(x);
+        } else {
+            block.set0(x);
+            model.remove(&x);
+        }
+    }
+
+    block.count1() == model.len()
+        && model.iter().enumerate().all(|(n, &x)| block.select1(n) == Some(x) && block.rank1(..x) == n)
+}
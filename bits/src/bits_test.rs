@@ -4,7 +4,8 @@ extern crate quickcheck_macros;
 use std::borrow::Cow;
 
 use bits::block::{Block, Buf, SmallSet, *};
-use bits::{BitVec, Bits};
+use bits::word::Word;
+use bits::{BitVec, Bits, BitsError, SpaceUsage};
 
 #[test]
 fn block_is_implemented() {
@@ -32,7 +33,9 @@ fn bits_is_implemented() {
     // _test::<Cow<[u8; 1000]>>();
     // _test::<Cow<Box<[u8; 2000]>>>();
     _test::<Buf<[u8; 100]>>();
+    _test::<SmallSet<u8, 10>>();
     _test::<SmallSet<u16, 10>>();
+    _test::<SmallSet<u32, 10>>();
 }
 
 #[quickcheck]
@@ -138,7 +141,7 @@ fn setup_bits(size: usize, mut bits: Vec<usize>) -> Vec<usize> {
 
 fn check<T>(size: usize, bits: Vec<usize>) -> bool
 where
-    T: Block + BlockMut + Rank + Select + Pack,
+    T: Block + BlockMut + Rank + Select,
 {
     let mut aux = none::<T>(size);
 
@@ -168,3 +171,304 @@ fn bits_boxed_array(bits: Vec<usize>) -> bool {
 
     check::<Box<[u64; 1024]>>(size, bits)
 }
+
+#[quickcheck]
+fn bits_sparse_blocks(bits: Vec<usize>) -> bool {
+    let size = 1 << 18;
+    let bits = setup_bits(size, bits);
+
+    check::<Buf<[u64; 8]>>(size, bits.clone()) && check::<SmallSet<u16, 8>>(size, bits)
+}
+
+fn from_blocks_imp<B: BlockMut + Select>(size: usize, bits: &[usize]) -> bool {
+    let mut blocks = vec![B::empty(); size / B::BITS];
+    let mut bv = BitVec::<u64>::new(size);
+    for &b in bits {
+        blocks[b / B::BITS].set1(b % B::BITS);
+        bv.set1(b);
+    }
+    let pop = bits::Pop::from(blocks);
+
+    pop.count1() == bits.len()
+        && bits.iter().enumerate().all(|(i, &b)| pop.rank1(..b) == i && pop.select1(i) == Some(b))
+        && (0..pop.count0()).step_by(997).all(|n| pop.select0(n) == bv.select0(n))
+}
+
+#[quickcheck]
+fn pop_from_blocks(bits: Vec<usize>) -> bool {
+    let size = 1 << 18;
+    let bits = setup_bits(size, bits);
+
+    from_blocks_imp::<Buf<[u64; 8]>>(size, &bits)
+        && from_blocks_imp::<SmallSet<u16, 8>>(size, &bits)
+        && from_blocks_imp::<SmallSet<u8, 8>>(size, &bits)
+        && from_blocks_imp::<Adaptive<8>>(size, &bits)
+        && from_blocks_imp::<[u32; 3]>(
+            3 * 32 * 1024,
+            &bits.iter().copied().filter(|&b| b < 3 * 32 * 1024).collect::<Vec<_>>(),
+        )
+}
+
+fn set_range_imp<T: Word>(ops: Vec<(bool, u16, u16)>) -> bool {
+    let mut bits = BitVec::<T>::new(1000);
+    let mut naive = vec![false; bits.bits()];
+    for (on, a, b) in ops {
+        let (s, e) = (a as usize % naive.len(), b as usize % naive.len());
+        let (s, e) = (s.min(e), s.max(e));
+        if on {
+            bits.set1_range(s..e);
+        } else {
+            bits.set0_range(s..e);
+        }
+        naive[s..e].iter_mut().for_each(|x| *x = on);
+    }
+    let ranks = (0..=naive.len()).step_by(13).all(|i| {
+        bits.rank1(i..) == naive[i..].iter().filter(|&&x| x).count()
+            && bits.rank1(..i) == naive[..i].iter().filter(|&&x| x).count()
+    });
+    naive.iter().enumerate().all(|(i, &x)| bits.test(i) == Some(x)) && ranks
+}
+
+#[quickcheck]
+fn set_range(ops: Vec<(bool, u16, u16)>) -> bool {
+    set_range_imp::<u8>(ops.clone()) && set_range_imp::<u64>(ops.clone()) && set_range_imp::<u128>(ops)
+}
+
+fn try_ops_imp<B: BlockMut + Select>(mut block: B, ops: &[usize]) -> bool {
+    ops.iter().all(|&i| {
+        let in_bounds = i < B::BITS;
+        let set = block.try_set1(i);
+        let rank = block.try_rank1(..=i);
+        let valid = if in_bounds {
+            set.is_ok() && rank == Ok(block.rank1(..=i)) && block.try_select1(rank.unwrap() - 1) == Ok(i)
+        } else {
+            set == Err(BitsError::OutOfBounds { index: i, len: B::BITS })
+                && rank == Err(BitsError::InvalidRange { start: 0, end: i + 1, len: B::BITS })
+        };
+        valid
+            && block.try_select1(block.count1())
+                == Err(BitsError::OutOfBounds { index: block.count1(), len: block.count1() })
+    })
+}
+
+#[quickcheck]
+fn try_ops(ops: Vec<u16>) -> bool {
+    let ops = ops.into_iter().map(|i| i as usize).collect::<Vec<_>>();
+    try_ops_imp(SmallSet::<u8, 4>::empty(), &ops)
+        && try_ops_imp(SmallSet::<u16, 4>::empty(), &ops)
+        && try_ops_imp(Buf::<[u64; 8]>::empty(), &ops)
+        && try_ops_imp(0u128, &ops)
+}
+
+#[test]
+fn try_assign() {
+    let mut a = BitVec::<u64>::new(128);
+    let b = BitVec::<u64>::new(256);
+    assert_eq!(a.try_or(&b), Err(BitsError::LengthMismatch { lhs: 128, rhs: 256 }));
+    let mut c = BitVec::<u64>::new(256);
+    c.set1(200);
+    assert_eq!(c.try_xor(&b), Ok(()));
+    assert_eq!(c.count1(), 1);
+}
+
+#[quickcheck]
+fn rank9_matches_pop(vec: Vec<u64>, dense: bool) -> bool {
+    let vec = if dense { vec.into_iter().map(|w| !w).collect() } else { vec };
+    let bv = BitVec::from(vec.clone());
+    let r9 = bits::Rank9::from(vec);
+
+    r9.bits() == bv.bits()
+        && r9.count1() == bv.count1()
+        && (0..=bv.bits()).step_by(7).all(|i| r9.rank1(..i) == bv.rank1(..i) && r9.rank0(i..) == bv.rank0(i..))
+        && (0..=bv.count1()).all(|n| r9.select1(n) == bv.select1(n))
+        && (0..=bv.count0()).all(|n| r9.select0(n) == bv.select0(n))
+}
+
+#[test]
+fn space_usage() {
+    let bv = BitVec::<u64>::new(1 << 16);
+    assert_eq!(bv.heap_bytes(), 1 << 13);
+
+    let pop = bits::Pop::from(vec![0u64; 1 << 10]);
+    let space = pop.space();
+    assert_eq!(space.repr, 1 << 13);
+    assert_eq!(pop.heap_bytes(), space.repr + space.ubs + space.lbs);
+    assert!(pop.bits_per_element(pop.bits()) > 1.0);
+
+    let r9 = bits::Rank9::from(vec![0u64; 1 << 10]);
+    assert_eq!(r9.heap_bytes(), (1 << 13) * 5 / 4);
+
+    let mut set = SmallSet::<u16, 4>::empty();
+    assert_eq!(set.heap_bytes(), 0);
+    (0..100).for_each(|i| set.set1(i * 3));
+    assert!(set.heap_bytes() >= 100 * 2);
+}
+
+#[test]
+fn pop_select0_past_the_end() {
+    let mut pop = bits::Pop::<u64>::new(1000);
+    pop.set1(3);
+    assert_eq!(pop.count0(), 1023);
+    assert_eq!(pop.select0(1022), Some(1023));
+    assert_eq!(pop.select0(1023), None);
+    assert_eq!(pop.select0(5000), None);
+}
+
+/// Compares two `RankSelect`s at every position.
+fn same_rank_select<A: ?Sized + bits::RankSelect, B: ?Sized + bits::RankSelect>(a: &A, b: &B) -> bool {
+    use bits::Excess;
+    a.bits() == b.bits()
+        && a.ones() == b.ones()
+        && (0..=a.bits()).all(|i| {
+            a.bit(i) == b.bit(i) && a.rank_ones(..i) == b.rank_ones(..i) && a.rank_zeros(i..) == b.rank_zeros(i..)
+        })
+        && (0..=a.ones()).all(|n| a.select_one(n) == b.select_one(n))
+        && (0..=a.zeros()).all(|n| a.select_zero(n) == b.select_zero(n))
+        && (0..a.bits())
+            .step_by(11)
+            .all(|i| a.excess_ones(i..) == b.excess_ones(i..) && a.excess_zeros(..i) == b.excess_zeros(..i))
+}
+
+#[quickcheck]
+fn elias_fano_matches_pop(ones: Vec<u16>, len: u16) -> bool {
+    let bits = len as usize % 8192 + 1;
+    let mut pop = bits::Pop::<u64>::new(bits);
+    ones.iter().for_each(|&i| pop.set1(i as usize % bits));
+
+    let ef = bits::EliasFano::from(pop.inner());
+    let bv = BitVec::from(pop.inner().as_slice().to_vec());
+    same_rank_select(&ef, &pop) && same_rank_select(&*bv, &bits::Rank9::from(bv.as_slice().to_vec()))
+}
+
+#[quickcheck]
+fn rank_select_on_blocks(block: u128, ops: Vec<u8>) -> bool {
+    let mut set = SmallSet::<u8, 8>::empty();
+    ops.iter().for_each(|&i| set.set1(i as usize));
+    same_rank_select(&block, Bits::new(&[block])) && same_rank_select(&set, &*BitVec::from(vec![set.clone()]))
+}
+
+#[quickcheck]
+fn wide_layout_matches_pop(ops: Vec<(bool, u16)>) -> bool {
+    // A few super blocks are enough, as the layouts differ only in how counts are packed.
+    const BITS: usize = 1 << 13;
+    let mut pop = bits::Pop::<u64>::new(BITS);
+    let mut wide = bits::Pop::<u64, BitVec<u64>, bits::WideL1L2>::new(BITS);
+    for &(set, i) in &ops {
+        let i = i as usize % BITS;
+        if set {
+            pop.set1(i);
+            wide.try_set1(i).unwrap();
+        } else {
+            pop.set0(i);
+            wide.set0(i);
+        }
+    }
+    let rebuilt = bits::Pop::<_, _, bits::WideL1L2>::try_from_repr(wide.inner()).unwrap();
+    same_rank_select(&pop, &wide) && same_rank_select(&wide, &rebuilt)
+}
+
+/// A block of which bits in `[0, n)` are set, to build a huge `Pop` without allocating the bits.
+#[derive(Debug, Clone, Copy)]
+struct Prefix(usize);
+
+impl Block for Prefix {
+    const BITS: usize = 1 << 20;
+    fn empty() -> Self {
+        Prefix(0)
+    }
+    fn test(&self, i: usize) -> Option<bool> {
+        (i < Self::BITS).then_some(i < self.0)
+    }
+}
+
+impl Count for Prefix {
+    fn count1(&self) -> usize {
+        self.0
+    }
+}
+
+impl Rank for Prefix {
+    fn rank1<R: std::ops::RangeBounds<usize>>(&self, r: R) -> usize {
+        let r = BitsError::check_range(&r, Self::BITS).unwrap();
+        r.end.min(self.0).saturating_sub(r.start)
+    }
+}
+
+impl Select for Prefix {}
+
+impl BlockMut for Prefix {
+    fn set1(&mut self, i: usize) {
+        assert!(i <= self.0, "Prefix can only grow by one");
+        self.0 = self.0.max(i + 1);
+    }
+    fn set0(&mut self, i: usize) {
+        assert!(i + 1 >= self.0, "Prefix can only shrink by one");
+        self.0 = self.0.min(i);
+    }
+}
+
+// Overflow on updates is checked by a hand-built `Aux` in bits_aux.
+// This builds whole upper blocks and takes minutes, so run it with `--ignored`.
+#[test]
+#[ignore = "builds 2^32 bits"]
+fn upper_block_all_set() {
+    const UB: usize = 1 << 32;
+    let mut blocks = vec![Prefix(Prefix::BITS); UB / Prefix::BITS];
+    blocks.push(Prefix(0));
+    let full = BitVec::from(blocks.clone());
+    *blocks.last_mut().unwrap() = Prefix(1);
+    blocks[UB / Prefix::BITS - 1] = Prefix(Prefix::BITS - 1);
+    let almost = BitVec::from(blocks);
+
+    assert_eq!(bits::Pop::<_, _, bits::L1L2>::try_from_repr(&*full).unwrap_err(), BitsError::Overflow);
+    let mut pop = bits::Pop::<_, _, bits::L1L2>::try_from_repr(almost.clone()).unwrap();
+    assert_eq!(pop.count1(), UB);
+    assert_eq!(pop.try_set1(UB - 1), Err(BitsError::Overflow));
+    assert_eq!(pop.test(UB - 1), Some(false));
+    assert_eq!(pop.rank1(..UB), UB - 1);
+    pop.set0(UB - 2);
+    assert_eq!(pop.select1(UB - 3), Some(UB - 3));
+    assert_eq!(pop.select1(UB - 2), Some(UB));
+    assert_eq!(pop.select0(0), Some(UB - 2));
+
+    let wide = bits::Pop::<_, _, bits::WideL1L2>::try_from_repr(&*full).unwrap();
+    assert_eq!(wide.count1(), UB);
+    assert_eq!(wide.rank1(..UB), UB);
+    assert_eq!(wide.select1(UB - 1), Some(UB - 1));
+    assert_eq!(wide.select0(0), Some(UB));
+
+    let mut wide = bits::Pop::<_, _, bits::WideL1L2>::try_from_repr(almost).unwrap();
+    wide.try_set1(UB - 1).unwrap();
+    assert_eq!(wide.rank1(..UB), UB);
+    assert_eq!(wide.select1(UB), Some(UB));
+}
+
+#[quickcheck]
+fn select_in_range(ones: Vec<u16>, ranges: Vec<(u16, u16, u8)>) -> bool {
+    let bits = 1 << 16;
+    let mut pop = bits::Pop::<u64>::new(bits);
+    ones.iter().for_each(|&i| pop.set1(i as usize));
+    let bv = BitVec::from(pop.inner().as_slice().to_vec());
+    let r9 = bits::Rank9::from(bv.as_slice().to_vec());
+    let block: [u64; 1024] = bv.as_slice().try_into().unwrap();
+
+    ranges.into_iter().all(|(s, e, n)| {
+        let (s, e, n) = (s.min(e) as usize, s.max(e) as usize, n as usize);
+        let want1 = (s..e).filter(|&i| pop.test(i) == Some(true)).nth(n);
+        let want0 = (s..e).filter(|&i| pop.test(i) == Some(false)).nth(n);
+        let after = (s..bits).filter(|&i| pop.test(i) == Some(true)).nth(n);
+
+        pop.select1_in(s..e, n) == want1
+            && pop.select0_in(s..e, n) == want0
+            && pop.select1_after(s, n) == after
+            && bv.select1_in(s..e, n) == want1
+            && bv.select0_in(s..e, n) == want0
+            && bv.select1_after(s, n) == after
+            && Select::select1_in(&block, s..e, n) == want1
+            && Select::select0_in(&block, s..e, n) == want0
+            && Select::select1_after(&block, s, n) == after
+            && bits::RankSelect::select_one_in(&r9, s..e, n) == want1
+            && bits::RankSelect::select_zero_in(&r9, s..e, n) == want0
+            && bits::RankSelect::select_one_after(&r9, s, n) == after
+    })
+}
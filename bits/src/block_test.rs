@@ -0,0 +1,54 @@
+#[macro_use(quickcheck)]
+extern crate quickcheck_macros;
+
+use bits::block::{Adaptive, Buf, SmallSet};
+use bits::word::U256;
+use bits_testkit::{check_assign, check_block, check_pack, Op};
+
+#[quickcheck]
+fn words(ops: Vec<Op>, packs: Vec<(usize, usize, u64)>) {
+    check_block::<u8>(&ops);
+    check_block::<u16>(&ops);
+    check_block::<u32>(&ops);
+    check_block::<u64>(&ops);
+    check_block::<u128>(&ops);
+    check_block::<U256>(&ops);
+
+    check_pack::<u32>(&ops, &packs);
+    check_pack::<u128>(&ops, &packs);
+    check_pack::<U256>(&ops, &packs);
+}
+
+#[quickcheck]
+fn arrays(ops: Vec<Op>, packs: Vec<(usize, usize, u64)>) {
+    check_block::<[u8; 3]>(&ops);
+    check_block::<[u64; 2]>(&ops);
+    check_block::<[u32; 33]>(&ops);
+    check_block::<Box<[u16; 8]>>(&ops);
+
+    check_pack::<[u8; 3]>(&ops, &packs);
+    check_pack::<[u64; 2]>(&ops, &packs);
+    check_pack::<Box<[u16; 8]>>(&ops, &packs);
+}
+
+#[quickcheck]
+fn sparse_blocks(ops: Vec<Op>, packs: Vec<(usize, usize, u64)>) {
+    check_block::<SmallSet<u8, 4>>(&ops);
+    check_block::<SmallSet<u16, 8>>(&ops);
+    check_block::<SmallSet<u32, 4>>(&ops);
+    check_block::<Buf<[u64; 8]>>(&ops);
+    check_block::<Adaptive<8>>(&ops);
+
+    check_pack::<Adaptive<8>>(&ops, &packs);
+}
+
+#[quickcheck]
+fn assign(lhs: Vec<Op>, rhs: Vec<Op>) {
+    check_assign::<u64>(&lhs, &rhs);
+    check_assign::<U256>(&lhs, &rhs);
+    check_assign::<SmallSet<u8, 4>>(&lhs, &rhs);
+    check_assign::<SmallSet<u16, 8>>(&lhs, &rhs);
+    check_assign::<SmallSet<u32, 4>>(&lhs, &rhs);
+    check_assign::<Buf<[u64; 8]>>(&lhs, &rhs);
+    check_assign::<Adaptive<8>>(&lhs, &rhs);
+}
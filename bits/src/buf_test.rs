@@ -0,0 +1,34 @@
+#[macro_use(quickcheck)]
+extern crate quickcheck_macros;
+
+use bits::block::*;
+
+fn cached_matches_uncached<B: bits::word::Word, const N: usize>(ones: Vec<usize>) -> bool {
+    let mut buf = Buf::<[B; N]>::empty();
+    for i in ones {
+        buf.set1(i % Buf::<[B; N]>::BITS);
+    }
+    let mut cached = buf.clone();
+    cached.build_cache();
+
+    let bits = Buf::<[B; N]>::BITS;
+    cached.count1() == buf.count1()
+        && (0..=bits).step_by(7).all(|i| cached.rank1(..i) == buf.rank1(..i) && cached.rank1(i..) == buf.rank1(i..))
+        && (0..buf.count1() + 1).all(|n| cached.select1(n) == buf.select1(n))
+        && (0..buf.count0() + 1).step_by(5).all(|n| cached.select0(n) == buf.select0(n))
+}
+
+#[quickcheck]
+fn cache_u64(ones: Vec<usize>) -> bool {
+    cached_matches_uncached::<u64, 20>(ones)
+}
+
+#[quickcheck]
+fn cache_u8(ones: Vec<usize>) -> bool {
+    cached_matches_uncached::<u8, 200>(ones)
+}
+
+#[quickcheck]
+fn cache_u128(ones: Vec<usize>) -> bool {
+    cached_matches_uncached::<u128, 3>(ones)
+}
@@ -0,0 +1,67 @@
+#[macro_use(quickcheck)]
+extern crate quickcheck_macros;
+
+use bits::codes::{Reader, Writer};
+use bits::word::Word;
+
+#[derive(Debug, Clone, Copy)]
+enum Code {
+    Bits(u64, usize),
+    Unary(u64),
+    Gamma(u64),
+    Delta(u64),
+    Rice(u64, usize),
+}
+
+fn codes(seed: Vec<(u8, u64)>) -> Vec<Code> {
+    seed.into_iter()
+        .map(|(c, n)| match c % 5 {
+            0 => Code::Bits(n & (!0 >> (64 - (c as usize % 65).max(1))), c as usize % 65),
+            1 => Code::Unary(n % 300),
+            2 => Code::Gamma(n.max(1)),
+            3 => Code::Delta(n.max(1)),
+            _ => Code::Rice(n % 1000, c as usize % 8),
+        })
+        .collect()
+}
+
+fn roundtrip<T: Word>(codes: &[Code]) -> bool {
+    let mut w = Writer::<T>::new();
+    for &c in codes {
+        match c {
+            Code::Bits(n, k) => w.write_bits(n, k),
+            Code::Unary(n) => w.write_unary(n),
+            Code::Gamma(n) => w.write_gamma(n),
+            Code::Delta(n) => w.write_delta(n),
+            Code::Rice(n, k) => w.write_rice(n, k),
+        }
+    }
+    let end = w.position();
+    let bits = w.into_bit_vec();
+
+    let mut r = Reader::new(&bits);
+    codes.iter().all(|&c| match c {
+        Code::Bits(n, k) => r.read_bits(k) == Some(if k == 0 { 0 } else { n }),
+        Code::Unary(n) => r.read_unary() == Some(n),
+        Code::Gamma(n) => r.read_gamma() == Some(n),
+        Code::Delta(n) => r.read_delta() == Some(n),
+        Code::Rice(n, k) => r.read_rice(k) == Some(n),
+    }) && r.position() == end
+        && r.read_unary().is_none()
+        && r.position() == end
+}
+
+#[quickcheck]
+fn roundtrip_u8(seed: Vec<(u8, u64)>) -> bool {
+    roundtrip::<u8>(&codes(seed))
+}
+
+#[quickcheck]
+fn roundtrip_u64(seed: Vec<(u8, u64)>) -> bool {
+    roundtrip::<u64>(&codes(seed))
+}
+
+#[quickcheck]
+fn roundtrip_u128(seed: Vec<(u8, u64)>) -> bool {
+    roundtrip::<u128>(&codes(seed))
+}
@@ -0,0 +1,127 @@
+#[macro_use(quickcheck)]
+extern crate quickcheck_macros;
+
+use std::collections::HashMap;
+
+use bits::filter::{Bloom, QuotientFilter};
+use bits::mask::helper::Assign;
+use bits::BitsError;
+
+#[quickcheck]
+fn bloom_no_false_negatives(xs: Vec<u32>, ys: Vec<u32>) -> bool {
+    let mut a = Bloom::new(1000, 4);
+    let mut b = Bloom::new(1000, 4);
+    xs.iter().for_each(|x| a.insert(x));
+    ys.iter().for_each(|y| b.insert(y));
+
+    let mut union = a.clone();
+    union.union(&b);
+    let mut inter = a.clone();
+    inter.intersect(&b);
+
+    xs.iter().all(|x| a.contains(x) && union.contains(x))
+        && ys.iter().all(|y| b.contains(y) && union.contains(y))
+        && xs.iter().filter(|x| ys.contains(x)).all(|x| inter.contains(x))
+        && Bloom::from_bytes(&union.to_bytes()) == Ok(union)
+}
+
+#[test]
+fn bloom_false_positive_rate() {
+    let mut bloom = Bloom::with_rate(10_000, 0.01);
+    for i in 0..10_000u64 {
+        bloom.insert(&i);
+    }
+    let fp = (10_000..110_000u64).filter(|i| bloom.contains(i)).count();
+    assert!(fp < 1500, "{fp} false positives in 100000");
+}
+
+#[test]
+#[should_panic]
+fn bloom_assign_different_shapes() {
+    let mut a = Bloom::new(1000, 4);
+    Assign::or(&mut a, &Bloom::new(1000, 3));
+}
+
+/// Applies `(insert, value)` to both a `QuotientFilter` and a map of counts.
+fn build(q: usize, r: usize, ops: &[(bool, u8)]) -> (QuotientFilter, HashMap<u8, usize>) {
+    let mut qf = QuotientFilter::new(q, r);
+    let mut counts = HashMap::<u8, usize>::new();
+    for &(insert, x) in ops {
+        if insert {
+            if qf.insert(&x).is_ok() {
+                *counts.entry(x).or_default() += 1;
+            } else {
+                assert_eq!(qf.len(), qf.capacity());
+            }
+        } else {
+            let c = counts.entry(x).or_default();
+            assert_eq!(qf.remove(&x), *c > 0, "remove({x})");
+            *c = c.saturating_sub(1);
+        }
+    }
+    (qf, counts)
+}
+
+#[quickcheck]
+fn quotient_filter_counts(ops: Vec<(bool, u8)>) -> bool {
+    // A small table wraps around often, and 58-bit remainders never collide in practice.
+    let (qf, counts) = build(6, 58, &ops);
+    qf.len() == counts.values().sum::<usize>()
+        && (0..=u8::MAX).all(|x| qf.count(&x) == counts.get(&x).copied().unwrap_or(0))
+        && QuotientFilter::from_bytes(&qf.to_bytes()) == Ok(qf)
+}
+
+#[quickcheck]
+fn quotient_filter_collisions(ops: Vec<(bool, u8)>) -> bool {
+    // Remainders of two bits collide often, counts can only be overestimated.
+    let mut qf = QuotientFilter::new(5, 2);
+    let mut counts = HashMap::<u8, usize>::new();
+    for (insert, x) in ops {
+        let c = counts.entry(x).or_default();
+        if insert {
+            if qf.insert(&x).is_ok() {
+                *c += 1;
+            }
+        } else if *c > 0 {
+            assert!(qf.remove(&x));
+            *c -= 1;
+        }
+    }
+    qf.len() == counts.values().sum::<usize>() && counts.iter().all(|(x, &c)| qf.count(x) >= c)
+}
+
+#[test]
+fn quotient_filter_full() {
+    let mut qf = QuotientFilter::new(3, 8);
+    for i in 0..7 {
+        qf.insert(&i).unwrap();
+    }
+    assert_eq!(qf.insert(&7), Err(BitsError::Overflow));
+    assert!((0..7).all(|i| qf.contains(&i)));
+    for i in 0..7 {
+        assert!(qf.remove(&i));
+    }
+    assert!(qf.is_empty());
+    assert_eq!(qf, QuotientFilter::new(3, 8));
+}
+
+#[test]
+fn quotient_filter_widest_remainders() {
+    // A slot of 61 + 3 bits fills a word.
+    let mut qf = QuotientFilter::new(3, 61);
+    for i in 0..7 {
+        qf.insert(&i).unwrap();
+    }
+    assert!((0..7).all(|i| qf.count(&i) == 1));
+    assert_eq!(QuotientFilter::from_bytes(&qf.to_bytes()).as_ref(), Ok(&qf));
+    for i in 0..7 {
+        assert!(qf.remove(&i));
+    }
+    assert!(qf.is_empty());
+}
+
+#[test]
+#[should_panic]
+fn quotient_filter_too_wide_remainders() {
+    QuotientFilter::new(2, 62);
+}
@@ -0,0 +1,39 @@
+#[macro_use(quickcheck)]
+extern crate quickcheck_macros;
+
+use bits::IntVec;
+
+fn matches_vec(width: u8, vals: Vec<u64>) -> bool {
+    let width = width as usize % 64 + 1;
+    let max = !0 >> (64 - width);
+    let vals = vals.into_iter().map(|v| v & max).collect::<Vec<_>>();
+
+    let mut pushed = IntVec::new(width);
+    vals.iter().for_each(|&v| pushed.push(v));
+    let sliced = IntVec::from_slice(width, &vals);
+
+    let mut set = sliced.clone();
+    for i in (0..vals.len()).step_by(3) {
+        set.set(i, max - vals[i]);
+    }
+    let mut popped = pushed.clone();
+    let last = popped.pop();
+
+    pushed == sliced
+        && sliced.iter().eq(vals.iter().copied())
+        && sliced.iter().rev().eq(vals.iter().rev().copied())
+        && (0..=vals.len()).all(|i| sliced.get(i) == vals.get(i).copied())
+        && (0..vals.len()).all(|i| set.get(i) == Some(if i % 3 == 0 { max - vals[i] } else { vals[i] }))
+        && last == vals.last().copied()
+        && popped == IntVec::from_slice(width, &vals[..vals.len().saturating_sub(1)])
+}
+
+#[quickcheck]
+fn intvec_any_width(width: u8, vals: Vec<u64>) -> bool {
+    matches_vec(width, vals)
+}
+
+#[quickcheck]
+fn intvec_aligned_width(width: u8, vals: Vec<u64>) -> bool {
+    matches_vec([1, 2, 4, 8, 16, 32, 64][width as usize % 7] - 1, vals)
+}
@@ -1,12 +1,23 @@
 //! `bits`
 
 pub mod block {
-    pub use bits_buf::Buf;
+    pub use bits_adaptive::Adaptive;
+    pub use bits_buf::{Buf, Pool};
     #[doc(inline)]
     pub use bits_core::block::*;
     pub use smallset::SmallSet;
 }
 
+pub mod codes {
+    #[doc(inline)]
+    pub use bits_codes::{Reader, Writer};
+}
+
+pub mod filter {
+    #[doc(inline)]
+    pub use bits_filter::{Bloom, QuotientFilter};
+}
+
 pub mod mask {
     #[doc(inline)]
     pub use bits_core::mask::*;
@@ -14,8 +25,10 @@ pub mod mask {
 
 pub mod word {
     #[doc(inline)]
-    pub use bits_core::word::Word;
+    pub use bits_core::word::{Lanes, Word, U256, U512};
 }
 
-pub use bits_aux::Pop;
-pub use bits_core::{BitVec, Bits};
+pub use bits_aux::{EliasFano, Layout, Pop, PopSpace, Rank9, WideL1L2, L1L2};
+pub use bits_core::{BitLen, BitVec, Bits, BitsError, Excess, RankSelect, SpaceUsage};
+pub use bits_intvec::IntVec;
+pub use rangeset::RangeSet;
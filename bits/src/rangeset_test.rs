@@ -0,0 +1,73 @@
+#[macro_use(quickcheck)]
+extern crate quickcheck_macros;
+
+use bits::mask::Mask;
+use bits::{BitVec, Pop, RangeSet};
+
+const LEN: usize = 1000;
+
+/// Applies `(insert, start, len)` to both a `RangeSet` and `Vec<bool>`.
+fn build(ops: &[(bool, u16, u8)]) -> (RangeSet, Vec<bool>) {
+    let mut set = RangeSet::new();
+    let mut naive = vec![false; LEN];
+    for &(insert, s, n) in ops {
+        let s = s as usize % LEN;
+        let e = (s + n as usize).min(LEN);
+        if insert {
+            set.insert_range(s..e);
+        } else {
+            set.remove_range(s..e);
+        }
+        naive[s..e].iter_mut().for_each(|x| *x = insert);
+    }
+    (set, naive)
+}
+
+#[quickcheck]
+fn normalized(ops: Vec<(bool, u16, u8)>) -> bool {
+    let (set, naive) = build(&ops);
+    let rs = set.ranges();
+    rs.iter().all(|r| !r.is_empty())
+        && rs.windows(2).all(|w| w[0].end < w[1].start)
+        && set == naive.iter().enumerate().filter(|(_, &x)| x).map(|(i, _)| i..i + 1).collect()
+}
+
+#[quickcheck]
+fn rank_select(ops: Vec<(bool, u16, u8)>) -> bool {
+    let (set, naive) = build(&ops);
+    let ones = naive.iter().enumerate().filter(|(_, &x)| x).map(|(i, _)| i).collect::<Vec<_>>();
+    let zeros = naive.iter().enumerate().filter(|(_, &x)| !x).map(|(i, _)| i).collect::<Vec<_>>();
+
+    set.count1() == ones.len()
+        && (0..LEN).all(|i| set.contains(i) == naive[i])
+        && (0..=LEN).step_by(7).all(|i| {
+            set.rank1(..i) == naive[..i].iter().filter(|&&x| x).count()
+                && set.rank0(i..LEN) == naive[i..].iter().filter(|&&x| !x).count()
+        })
+        && (0..=ones.len()).all(|n| set.select1(n) == ones.get(n).copied())
+        && zeros.iter().enumerate().all(|(n, &p)| set.select0(n) == Some(p))
+}
+
+#[quickcheck]
+fn to_and_from_bits(ops: Vec<(bool, u16, u8)>) -> bool {
+    let (set, _) = build(&ops);
+    let bv = BitVec::<u32>::from(&set);
+    let pop = Pop::<u64>::from(&set);
+    RangeSet::from(&*bv) == set && RangeSet::from(&pop) == set && pop.count1() == set.count1()
+}
+
+#[quickcheck]
+fn mask(a: Vec<(bool, u16, u8)>, b: Vec<(bool, u16, u8)>) -> bool {
+    let (a, x) = build(&a);
+    let (b, y) = build(&b);
+    let mut bv = BitVec::<u64>::new(LEN);
+    y.iter().enumerate().filter(|(_, &v)| v).for_each(|(i, _)| bv.set1(i));
+
+    let naive = |f: fn(bool, bool) -> bool| -> RangeSet {
+        x.iter().zip(&y).enumerate().filter(|(_, (&p, &q))| f(p, q)).map(|(i, _)| i..i + 1).collect()
+    };
+    RangeSet::from_blocks(a.blocks::<u64>().and(bv.as_slice())) == naive(|p, q| p & q)
+        && RangeSet::from_blocks(a.blocks::<u64>().or(b.blocks::<u64>())) == naive(|p, q| p | q)
+        && RangeSet::from_blocks(a.blocks::<u64>().xor(bv.as_slice())) == naive(|p, q| p ^ q)
+        && RangeSet::from_blocks(a.blocks::<u64>().not(b.blocks::<u64>())) == naive(|p, q| p & !q)
+}
@@ -0,0 +1,101 @@
+#[macro_use(quickcheck)]
+extern crate quickcheck_macros;
+
+use std::collections::BTreeSet;
+
+use bits::block::*;
+use bits::mask::helper::Assign;
+
+fn smallset<T, const N: usize>(vec: &[usize]) -> SmallSet<T, N>
+where
+    SmallSet<T, N>: BlockMut,
+{
+    let mut set = SmallSet::empty();
+    for &i in vec {
+        set.set1(i % SmallSet::<T, N>::BITS);
+    }
+    set
+}
+
+fn model<const BITS: usize>(vec: &[usize]) -> BTreeSet<usize> {
+    vec.iter().map(|&i| i % BITS).collect()
+}
+
+fn ones<T: Select>(b: &T) -> Vec<usize> {
+    (0..b.count1()).map(|n| b.select1(n).unwrap()).collect()
+}
+
+macro_rules! assign_matches_model {
+    ($name:ident, $Key:ty, $BITS:expr) => {
+        #[quickcheck]
+        fn $name(a: Vec<usize>, b: Vec<usize>) -> bool {
+            // Make `b` much larger than `a` so that galloping skips long runs.
+            let b = b.iter().flat_map(|&x| (0..64).map(move |d| x.wrapping_add(d * 7))).collect::<Vec<_>>();
+
+            let (x, y) = (smallset::<$Key, 4>(&a), smallset::<$Key, 4>(&b));
+            let (p, q) = (model::<{ $BITS }>(&a), model::<{ $BITS }>(&b));
+
+            let check = |f: fn(&mut SmallSet<$Key, 4>, &SmallSet<$Key, 4>), r: BTreeSet<usize>| {
+                let mut s = x.clone();
+                f(&mut s, &y);
+                s.count1() == r.len() && ones(&s) == r.into_iter().collect::<Vec<_>>()
+            };
+
+            check(Assign::and, p.intersection(&q).copied().collect())
+                && check(Assign::not, p.difference(&q).copied().collect())
+                && check(Assign::or, p.union(&q).copied().collect())
+                && check(Assign::xor, p.symmetric_difference(&q).copied().collect())
+        }
+    };
+}
+
+assign_matches_model!(assign_u8, u8, 1 << 8);
+assign_matches_model!(assign_u16, u16, 1 << 16);
+assign_matches_model!(assign_u32, u32, 1 << 32);
+
+#[quickcheck]
+fn rank_select_u32(vec: Vec<u32>) -> bool {
+    let set = smallset::<u32, 8>(&vec.iter().map(|&x| x as usize).collect::<Vec<_>>());
+    let model = vec.iter().map(|&x| x as usize).collect::<BTreeSet<_>>();
+    model
+        .iter()
+        .enumerate()
+        .all(|(i, &x)| set.rank1(..x) == i && set.select1(i) == Some(x) && set.test(x) == Some(true))
+}
+
+#[quickcheck]
+fn from_sorted_iter_matches_model(vec: Vec<u16>) -> bool {
+    let model = vec.iter().map(|&x| x as usize).collect::<BTreeSet<_>>();
+    // Duplicates are allowed as long as indices are sorted.
+    let mut sorted = vec.iter().map(|&x| x as usize).collect::<Vec<_>>();
+    sorted.sort_unstable();
+    let set = SmallSet::<u16, 4>::from_sorted_iter(sorted);
+    set.count1() == model.len() && ones(&set) == model.into_iter().collect::<Vec<_>>()
+}
+
+#[quickcheck]
+fn from_unsorted_matches_model(vec: Vec<u16>) -> bool {
+    let model = vec.iter().map(|&x| x as usize).collect::<BTreeSet<_>>();
+    let set = SmallSet::<u16, 4>::from_unsorted(vec.iter().map(|&x| x as usize));
+    set.count1() == model.len() && ones(&set) == model.into_iter().collect::<Vec<_>>()
+}
+
+#[quickcheck]
+fn extend_sorted_matches_model(a: Vec<u16>, b: Vec<u16>) -> bool {
+    let a = a.iter().map(|&x| x as usize).collect::<Vec<_>>();
+    let b = b.iter().map(|&x| x as usize).collect::<BTreeSet<_>>();
+    let mut set = smallset::<u16, 4>(&a);
+    set.extend_sorted(b.iter().copied());
+    let model = model::<{ 1 << 16 }>(&a).union(&b).copied().collect::<Vec<_>>();
+    set.count1() == model.len() && ones(&set) == model
+}
+
+#[quickcheck]
+fn remove_range_matches_model(vec: Vec<u16>, s: u16, e: u16) -> bool {
+    let vec = vec.iter().map(|&x| x as usize).collect::<Vec<_>>();
+    let (s, e) = (s.min(e) as usize, s.max(e) as usize);
+    let mut set = smallset::<u16, 4>(&vec);
+    set.remove_range(s..e);
+    let model = model::<{ 1 << 16 }>(&vec).into_iter().filter(|x| !(s..e).contains(x)).collect::<Vec<_>>();
+    set.count1() == model.len() && ones(&set) == model
+}
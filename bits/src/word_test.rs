@@ -27,3 +27,72 @@ fn msb_work() {
     assert_eq!(0b_0000_0000_u8.msb(), 0b_0000_0000_u8);
     assert_eq!(0b_0101_1101_u8.msb(), 0b_0100_0000_u8);
 }
+
+mod lanes {
+    use bits::word::{Lanes, Word, U256};
+    use bits::{BitVec, Pop};
+    use num::{CheckedDiv, Num, PrimInt};
+
+    use super::*;
+
+    fn u128(w: Lanes<2>) -> u128 {
+        (w.0[1] as u128) << 64 | w.0[0] as u128
+    }
+
+    fn lanes(x: u128) -> Lanes<2> {
+        Lanes([x as u64, (x >> 64) as u64])
+    }
+
+    #[quickcheck]
+    fn arith_matches_u128(a: u128, b: u128, n: u8) -> bool {
+        let (x, y) = (lanes(a), lanes(b));
+        let n = n as usize;
+        u128(x + y) == a.wrapping_add(b)
+            && u128(x - y) == a.wrapping_sub(b)
+            && u128(x * y) == a.wrapping_mul(b)
+            && x.checked_div(&y).map(u128) == a.checked_div(b)
+            && (b == 0 || u128(x % y) == a % b)
+            && u128(x << n) == a.checked_shl(n as u32).unwrap_or(0)
+            && u128(x >> n) == a.checked_shr(n as u32).unwrap_or(0)
+            && u128(x.rotate_left(n as u32)) == a.rotate_left(n as u32)
+            && u128(x.swap_bytes()) == a.swap_bytes()
+            && x.cmp(&y) == a.cmp(&b)
+    }
+
+    #[quickcheck]
+    fn bits_match_u128(a: u128, i: u8) -> bool {
+        let x = lanes(a);
+        let i = i as usize;
+        x.count1() == a.count1()
+            && x.leading_zeros() == a.leading_zeros()
+            && x.trailing_zeros() == a.trailing_zeros()
+            && x.rank1(..i.min(128)) == a.rank1(..i.min(128))
+            && x.select1(i) == a.select1(i)
+            && x.select0(i) == a.select0(i)
+            && u128(x.lsb()) == a.lsb()
+            && u128(x.msb()) == a.msb()
+            && x.unpack::<u32>(i % 128, 32.min(128 - i % 128)) == a.unpack::<u32>(i % 128, 32.min(128 - i % 128))
+    }
+
+    #[quickcheck]
+    fn fmt_matches_u128(a: u128) -> bool {
+        let x = lanes(a);
+        x.to_string() == a.to_string()
+            && format!("{:#b}", x) == format!("{:#b}", a)
+            && format!("{:x}", x) == format!("{:x}", a)
+            && format!("{:X}", x) == format!("{:X}", a)
+            && Lanes::<2>::from_str_radix(&a.to_string(), 10) == Ok(x)
+    }
+
+    #[quickcheck]
+    fn pop_select_matches_u64(vec: Vec<u64>) -> bool {
+        let mut words = vec.clone();
+        words.resize(vec.len().div_ceil(4) * 4, 0);
+        let wide = words.chunks(4).map(|c| U256::from([c[0], c[1], c[2], c[3]])).collect::<Vec<_>>();
+        let pop = Pop::from(wide);
+        let bv = BitVec::from(words);
+        (0..bv.count1()).all(|n| pop.select1(n) == bv.select1(n))
+            && (0..bv.count0()).step_by(7).all(|n| pop.select0(n) == bv.select0(n))
+            && (0..=bv.bits()).step_by(11).all(|i| pop.rank1(..i) == bv.rank1(..i))
+    }
+}